@@ -0,0 +1,34 @@
+use std::path::Path;
+
+/// Tells the generated answer tests which parts have an expected answer: `answers.toml` entry
+/// `[day_5.part2] real = ".."` sets the cfg `day05 = "part2_real"`, the tests without one are ignored
+fn main() {
+    let answers_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/answers.toml");
+    println!("cargo::rerun-if-changed={}", answers_path.display());
+
+    let mut values = vec![];
+    for part in ["part1", "part2"] {
+        for kind in ["test", "real"] {
+            values.push(format!("\"{}_{}\"", part, kind));
+        }
+    }
+    for day in 1..=25 {
+        println!("cargo::rustc-check-cfg=cfg(day{:02}, values({}))", day, values.join(", "));
+    }
+
+    let Ok(content) = std::fs::read_to_string(&answers_path) else {
+        return;
+    };
+    let answers: toml::Table = content.parse().expect("answers.toml should be valid TOML");
+    for (day_key, parts) in &answers {
+        let Some(day) = day_key.strip_prefix("day_").and_then(|d| d.parse::<u8>().ok()) else {
+            continue;
+        };
+        for (part, datasets) in parts.as_table().into_iter().flatten() {
+            for dataset in datasets.as_table().into_iter().flat_map(|d| d.keys()) {
+                let kind = if dataset.starts_with("test") { "test" } else if dataset == "real" { "real" } else { continue };
+                println!("cargo::rustc-cfg=day{:02}=\"{}_{}\"", day, part, kind);
+            }
+        }
+    }
+}
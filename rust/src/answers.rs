@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::utils::{self, Answer, Dataset, Part};
+
+lazy_static! {
+    static ref ANSWERS: Mutex<AnswerStore> = Mutex::new(AnswerStore::load(answers_file()));
+}
+
+pub fn answers_file() -> PathBuf {
+    utils::data_dir().join("answers.toml")
+}
+
+/// Expected answers, stored in `answers.toml` as:
+/// ```toml
+/// [day_1.part1]
+/// test = "11"
+/// test_a = "4"
+/// real = "1882714"
+/// ```
+/// The document is edited in place so that its comments and layout are kept on save
+pub struct AnswerStore {
+    path: PathBuf,
+    content: DocumentMut,
+}
+
+impl AnswerStore {
+    pub fn load(path: PathBuf) -> AnswerStore {
+        let content = match fs::read_to_string(&path) {
+            Ok(text) => text
+                .parse::<DocumentMut>()
+                .unwrap_or_else(|e| panic!("Invalid answers file {}: {}", path.display(), e)),
+            Err(_) => DocumentMut::new(),
+        };
+        AnswerStore { path, content }
+    }
+
+    fn day_key(day: u8) -> String {
+        format!("day_{}", day)
+    }
+
+    fn part_key(part: Part) -> &'static str {
+        match part {
+            Part::Part1 => "part1",
+            Part::Part2 => "part2",
+        }
+    }
+
+    pub fn get(&self, day: u8, part: Part, data_set: &Dataset) -> Option<Answer> {
+        let value = self
+            .content
+            .get(&AnswerStore::day_key(day))?
+            .get(AnswerStore::part_key(part))?
+            .get(data_set.name())?;
+        match value.as_str() {
+            Some(s) => Some(Answer::from(s)),
+            None => Some(Answer::from(value.as_value()?.to_string().trim())),
+        }
+    }
+
+    pub fn set(&mut self, day: u8, part: Part, data_set: &Dataset, answer: &Answer) {
+        let day_table = self.content.entry(&AnswerStore::day_key(day)).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+        let part_table = day_table
+            .as_table_mut()
+            .expect("day entry should be a table")
+            .entry(AnswerStore::part_key(part))
+            .or_insert_with(|| Item::Table(Table::new()));
+        part_table
+            .as_table_mut()
+            .expect("part entry should be a table")
+            .insert(&data_set.name(), toml_edit::value(answer.as_str()));
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = fs::write(&self.path, self.content.to_string()) {
+            println!("Cannot write answers file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+pub fn expected_answer(day: u8, part: Part, data_set: &Dataset) -> Option<Answer> {
+    ANSWERS.lock().unwrap().get(day, part, data_set)
+}
+
+pub fn record_answer(day: u8, part: Part, data_set: &Dataset, answer: &Answer) {
+    let mut store = ANSWERS.lock().unwrap();
+    store.set(day, part, data_set, answer);
+    store.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_keeps_the_comments() {
+        let path = std::env::temp_dir().join(format!("answers_{}.toml", std::process::id()));
+        fs::write(&path, "# Recorded answers\n[day_1.part1]\ntest = \"142\" # from the example\n").unwrap();
+
+        let mut store = AnswerStore::load(path.clone());
+        store.set(1, Part::Part1, &Dataset::Real, &Answer::from("54331"));
+        store.set(2, Part::Part2, &Dataset::Test, &Answer::from("8"));
+        store.save();
+
+        let text = fs::read_to_string(&path).unwrap();
+        let store = AnswerStore::load(path.clone());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(store.get(1, Part::Part1, &Dataset::Test), Some(Answer::from("142")));
+        assert_eq!(store.get(1, Part::Part1, &Dataset::Real), Some(Answer::from("54331")));
+        assert_eq!(
+            text,
+            "# Recorded answers\n[day_1.part1]\ntest = \"142\" # from the example\nreal = \"54331\"\n\n[day_2.part2]\ntest = \"8\"\n"
+        );
+    }
+}
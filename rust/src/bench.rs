@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound of measured iterations when running to a target wall time
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Directory of the saved bench results (in the crate directory), one `<revision>.json` file per git revision
+pub const BENCH_DIR: &str = "bench";
+
+lazy_static! {
+    static ref RESULTS: Mutex<BTreeMap<String, BenchStats>> = Mutex::new(BTreeMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchConfig {
+    /// Minimal number of measured iterations
+    pub iterations: u16,
+    /// Iterations run before measuring (not part of the statistics)
+    pub warmup: u16,
+    /// When set, keeps iterating until the measured iterations reach this wall time
+    pub target_ms: Option<u64>,
+}
+
+impl BenchConfig {
+    pub fn new(iterations: u16) -> BenchConfig {
+        BenchConfig {
+            iterations: iterations.max(1),
+            warmup: (iterations / 10).max(1),
+            target_ms: None,
+        }
+    }
+
+    pub fn with_warmup(&self, warmup: u16) -> BenchConfig {
+        BenchConfig { warmup, ..*self }
+    }
+
+    pub fn with_target(&self, target_ms: u64) -> BenchConfig {
+        BenchConfig {
+            target_ms: Some(target_ms),
+            ..*self
+        }
+    }
+
+    fn should_continue(&self, count: usize, elapsed: Duration) -> bool {
+        if count < self.iterations as usize {
+            return true;
+        }
+        match self.target_ms {
+            Some(target) => count < MAX_ITERATIONS && elapsed < Duration::from_millis(target),
+            None => false,
+        }
+    }
+}
+
+/// Statistics of the measured iterations (in ms), computed once the outliers are rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchStats {
+    pub iterations: usize,
+    pub warmup: u16,
+    pub rejected: usize,
+    pub min: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], ratio: f64) -> f64 {
+    let rank = (ratio * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl BenchStats {
+    /// Rejects the samples outside of the Tukey fences (1.5 interquartile range around the quartiles) before computing the statistics
+    pub fn from_samples(samples: &[f64], warmup: u16) -> BenchStats {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        if sorted.len() >= 4 {
+            let q1 = percentile(&sorted, 0.25);
+            let q3 = percentile(&sorted, 0.75);
+            let iqr = q3 - q1;
+            sorted.retain(|v| *v >= q1 - 1.5 * iqr && *v <= q3 + 1.5 * iqr);
+        }
+        let count = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / count;
+        let variance = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / count;
+        BenchStats {
+            iterations: samples.len(),
+            warmup,
+            rejected: samples.len() - sorted.len(),
+            min: sorted[0],
+            median: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            max: sorted[sorted.len() - 1],
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+impl Display for BenchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:.3} ms, median {:.3} ms, p95 {:.3} ms, max {:.3} ms, mean {:.3} ms (± {:.3}) for #{} iterations ({} warmup, {} outliers rejected)",
+            self.min, self.median, self.p95, self.max, self.mean, self.std_dev, self.iterations, self.warmup, self.rejected
+        )
+    }
+}
+
+/// Runs the warmup iterations then times each iteration, returning the last result with the statistics
+pub fn bench<R>(config: &BenchConfig, fct: impl Fn() -> R) -> (R, BenchStats) {
+    for _ in 0..config.warmup {
+        std::hint::black_box(fct());
+    }
+
+    let mut samples = vec![];
+    let start = Instant::now();
+    let mut res;
+    loop {
+        let start_iteration = Instant::now();
+        res = std::hint::black_box(fct());
+        samples.push(start_iteration.elapsed().as_secs_f64() * 1000.0);
+        if !config.should_continue(samples.len(), start.elapsed()) {
+            break;
+        }
+    }
+    (res, BenchStats::from_samples(&samples, config.warmup))
+}
+
+/// Keeps the statistics of a run (keyed by `day_N/part/dataset`) for the save and the comparison at the end of the runs
+pub fn store_result(key: String, stats: &BenchStats) {
+    RESULTS.lock().unwrap().insert(key, stats.clone());
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).current_dir(env!("CARGO_MANIFEST_DIR")).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Short revision of HEAD
+pub fn head_revision() -> String {
+    git(&["rev-parse", "--short", "HEAD"]).unwrap_or("unknown".to_string())
+}
+
+/// Short revision of HEAD, suffixed by `-dirty` when the working tree has local changes
+pub fn current_revision() -> String {
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"]).map(|s| !s.is_empty()).unwrap_or(false);
+    if dirty {
+        format!("{}-dirty", head_revision())
+    } else {
+        head_revision()
+    }
+}
+
+/// Resolved from the crate directory rather than the current one, like the data directory
+fn bench_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(BENCH_DIR)
+}
+
+fn bench_file(revision: &str) -> PathBuf {
+    bench_dir().join(format!("{}.json", revision))
+}
+
+fn load(revision: &str) -> Option<BTreeMap<String, BenchStats>> {
+    let content = fs::read_to_string(bench_file(revision)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(results) => Some(results),
+        Err(e) => {
+            println!("[BENCH] Invalid bench file {}: {}", bench_file(revision).display(), e);
+            None
+        }
+    }
+}
+
+/// Saves the results of the current runs, merged with the ones already saved for the same revision
+pub fn save(revision: &str) {
+    let mut saved = load(revision).unwrap_or_default();
+    saved.extend(RESULTS.lock().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())));
+    let path = bench_file(revision);
+    let result = fs::create_dir_all(bench_dir()).and_then(|_| fs::write(&path, serde_json::to_string_pretty(&saved).unwrap()));
+    match result {
+        Ok(_) => println!("[BENCH] {} results saved in {}", saved.len(), path.display()),
+        Err(e) => println!("[BENCH] Cannot write {}: {}", path.display(), e),
+    }
+}
+
+/// Compares the medians of the current runs with the baseline revision and returns the number of runs slower than the threshold (in %)
+pub fn compare(baseline_revision: &str, threshold: f64) -> usize {
+    let Some(baseline) = load(baseline_revision) else {
+        println!("[BENCH] No saved results for baseline {}", baseline_revision);
+        return 0;
+    };
+    let mut nb_regressions = 0;
+    println!("[BENCH] Comparison with {} (threshold {}%)", baseline_revision, threshold);
+    for (key, stats) in RESULTS.lock().unwrap().iter() {
+        match baseline.get(key) {
+            Some(base) => {
+                let delta = (stats.median - base.median) / base.median * 100.0;
+                let status = if stats.median > base.median * (1.0 + threshold / 100.0) {
+                    nb_regressions += 1;
+                    "SLOWER"
+                } else if stats.median < base.median * (1.0 - threshold / 100.0) {
+                    "FASTER"
+                } else {
+                    "SAME"
+                };
+                println!(
+                    "[BENCH] {:<24} median {:>10.3} ms vs {:>10.3} ms ({:+.1}%) {}",
+                    key, stats.median, base.median, delta, status
+                );
+            }
+            None => println!("[BENCH] {:<24} median {:>10.3} ms (no baseline)", key, stats.median),
+        }
+    }
+    nb_regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_is_nearest_rank() {
+        let sorted: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 0.95), 19.0);
+        assert_eq!(percentile(&sorted, 0.5), 10.0);
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 1.0), 20.0);
+        assert_eq!(percentile(&sorted[..10], 0.95), 10.0);
+    }
+
+    #[test]
+    fn outliers_are_rejected() {
+        let samples = [4.0, 1.0, 100.0, 2.0, 3.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let stats = BenchStats::from_samples(&samples, 2);
+        assert_eq!((stats.iterations, stats.warmup, stats.rejected), (10, 2, 1));
+        assert_eq!((stats.min, stats.median, stats.p95, stats.max), (1.0, 5.0, 9.0, 9.0));
+        assert_eq!(stats.mean, 5.0);
+        assert!((stats.std_dev - (60.0f64 / 9.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fewer_than_4_samples_are_all_kept() {
+        let stats = BenchStats::from_samples(&[10.0, 1.0, 1000.0], 0);
+        assert_eq!((stats.iterations, stats.rejected), (3, 0));
+        assert_eq!((stats.min, stats.median, stats.p95, stats.max), (1.0, 10.0, 1000.0, 1000.0));
+        assert_eq!(stats.mean, 337.0);
+
+        let stats = BenchStats::from_samples(&[2.5], 0);
+        assert_eq!((stats.min, stats.median, stats.p95, stats.max, stats.std_dev), (2.5, 2.5, 2.5, 2.5, 0.0));
+    }
+}
@@ -0,0 +1,380 @@
+use std::env;
+use std::fs::File;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::bench::BenchConfig;
+use crate::fetch::{self, Fetcher};
+use crate::logger::{Logger, Target};
+use crate::utils::{Dataset, DaysRestriction, InputSource, LogLevel, Part, RunOption};
+
+#[derive(Parser, Debug)]
+#[command(name = "aoc", about = "Advent of code runner")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum Command {
+    /// Run the puzzles (all days by default)
+    Run(RunArgs),
+    /// List the registered days
+    List,
+    /// Download the missing inputs and examples into the data directory
+    Fetch(FetchArgs),
+    /// Submit the answer of a part (computed on the real input when not given)
+    Submit(SubmitArgs),
+    /// Re-run the days affected by each change of the sources or of the data files
+    Watch(RunArgs),
+    /// Create a day from the template, register it and create its empty data files
+    NewDay {
+        /// Day number (1 to 25)
+        day: u8,
+    },
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command::Run(RunArgs::default())
+    }
+}
+
+#[derive(Args, Debug, Default, Clone)]
+pub struct RunArgs {
+    /// Days to run (comma separated or repeated), all days if omitted
+    #[arg(short, long, value_delimiter = ',')]
+    pub day: Vec<u8>,
+
+    /// Only run the given part (1 or 2)
+    #[arg(short, long, value_parser = parse_part)]
+    pub part: Option<Part>,
+
+    /// Only run the given dataset (test for all the tests, test_<name> for a named test, or real)
+    #[arg(long, value_parser = parse_dataset)]
+    pub dataset: Option<Dataset>,
+
+    /// Read the input from the given file (or stdin for -) instead of the datasets of the data directory
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Bench each puzzle with at least the given number of measured iterations
+    #[arg(short, long)]
+    pub bench: Option<u16>,
+
+    /// Number of warmup iterations in bench mode (a tenth of the iterations by default)
+    #[arg(long, requires = "bench")]
+    pub warmup: Option<u16>,
+
+    /// Keep iterating in bench mode until the measured iterations reach the given time (in ms)
+    #[arg(long, requires = "bench")]
+    pub bench_time: Option<u64>,
+
+    /// Run the days in parallel, on the given number of threads (the number of CPUs by default)
+    #[arg(long, num_args = 0..=1, default_missing_value = "0")]
+    pub parallel: Option<usize>,
+
+    /// Fail the runs not finished after the given number of seconds
+    #[arg(long)]
+    pub timeout: Option<f64>,
+
+    /// Enable debug logs (except for the days with a --log-day level)
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Log level of the days without override (error, info, debug or trace)
+    #[arg(long, value_parser = parse_log_level)]
+    pub log_level: Option<LogLevel>,
+
+    /// Log level override of a day, as DAY=LEVEL (comma separated or repeated)
+    #[arg(long, value_delimiter = ',', value_parser = parse_day_log_level)]
+    pub log_day: Vec<(u8, LogLevel)>,
+
+    /// Write the logs in the given file instead of stdout
+    #[arg(long)]
+    pub log_file: Option<String>,
+
+    /// Write the logs on stderr instead of stdout
+    #[arg(long, conflicts_with = "log_file")]
+    pub log_stderr: bool,
+
+    /// Prefix the log lines with the time
+    #[arg(long)]
+    pub log_timestamps: bool,
+
+    /// Keep the logs of each run in the report
+    #[arg(long)]
+    pub capture_logs: bool,
+
+    /// Implementation variants to bench side by side (comma separated, see `Context::bench_tag`)
+    #[arg(long, value_delimiter = ',', requires = "bench")]
+    pub bench_tags: Vec<String>,
+
+    /// Save the bench results under the current git revision (in the bench directory)
+    #[arg(long, requires = "bench")]
+    pub save_bench: bool,
+
+    /// Compare the bench results with the ones saved for the given revision (HEAD by default)
+    #[arg(long, requires = "bench", num_args = 0..=1, default_missing_value = "HEAD")]
+    pub baseline: Option<String>,
+
+    /// Slowdown (in %) of the median above which a run is flagged as a regression
+    #[arg(long, default_value_t = 10.0)]
+    pub threshold: f64,
+
+    /// Write the report of the runs as JSON in the given file
+    #[arg(long)]
+    pub report_json: Option<String>,
+
+    /// Write the report of the runs as JUnit XML in the given file
+    #[arg(long)]
+    pub report_junit: Option<String>,
+
+    /// Record the answers of the parts without expected answer (once confirmed)
+    #[arg(long)]
+    pub record: bool,
+}
+
+/// Access to the advent of code server (or a local server standing in for it)
+#[derive(Args, Debug)]
+pub struct ServerArgs {
+    /// Session token (the AOC_SESSION environment variable by default)
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Server to fetch from (the AOC_BASE_URL environment variable, or the advent of code site, by default)
+    #[arg(long)]
+    pub base_url: Option<String>,
+
+    /// Year of the puzzles (the AOC_YEAR environment variable by default), required to fetch or submit
+    #[arg(long)]
+    pub year: Option<u16>,
+
+    /// Minimal delay between two requests (in seconds)
+    #[arg(long, default_value_t = fetch::MIN_DELAY.as_secs_f64())]
+    pub min_delay: f64,
+}
+
+impl ServerArgs {
+    pub fn fetcher(&self) -> Fetcher {
+        let base_url = self.base_url.clone().or(env::var("AOC_BASE_URL").ok()).unwrap_or(fetch::BASE_URL.to_string());
+        let session = self.session.clone().or(env::var("AOC_SESSION").ok()).filter(|s| !s.is_empty());
+        let fetcher = Fetcher::new(&base_url, session).with_min_delay(Duration::from_secs_f64(self.min_delay));
+        match self.year.or(env::var("AOC_YEAR").ok().and_then(|y| y.parse().ok())) {
+            Some(year) => fetcher.with_year(year),
+            None => fetcher,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FetchArgs {
+    /// Days to fetch (comma separated or repeated), all the registered days if omitted
+    #[arg(short, long, value_delimiter = ',')]
+    pub day: Vec<u8>,
+
+    /// Only fetch the real inputs, not the examples
+    #[arg(long)]
+    pub no_examples: bool,
+
+    #[command(flatten)]
+    pub server: ServerArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct SubmitArgs {
+    /// Day of the answer
+    #[arg(short, long)]
+    pub day: u8,
+
+    /// Part of the answer (1 or 2)
+    #[arg(short, long, value_parser = parse_part)]
+    pub part: Part,
+
+    /// Answer to submit, computed on the real input when omitted
+    pub answer: Option<String>,
+
+    #[command(flatten)]
+    pub server: ServerArgs,
+}
+
+impl RunArgs {
+    pub fn days_restriction(&self) -> Option<Vec<u8>> {
+        if self.day.is_empty() {
+            None
+        } else {
+            Some(self.day.clone())
+        }
+    }
+
+    fn bench_config(&self) -> Option<BenchConfig> {
+        let mut config = BenchConfig::new(self.bench?);
+        if let Some(warmup) = self.warmup {
+            config = config.with_warmup(warmup);
+        }
+        if let Some(target_ms) = self.bench_time {
+            config = config.with_target(target_ms);
+        }
+        Some(config)
+    }
+
+    pub fn logger(&self) -> Result<Logger, String> {
+        let target = match &self.log_file {
+            Some(path) => Target::File(File::create(path).map_err(|e| format!("Cannot create log file {}: {}", path, e))?),
+            None if self.log_stderr => Target::Stderr,
+            None => Target::Stdout,
+        };
+        Ok(Logger {
+            level: self.log_level.unwrap_or(LogLevel::INFO),
+            day_levels: self.log_day.iter().copied().collect(),
+            target,
+            timestamps: self.log_timestamps,
+            capture: self.capture_logs,
+        })
+    }
+
+    /// Command line arguments of the `run` command giving these arguments back
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["run".to_string()];
+        let mut push = |name: &str, value: Option<String>| {
+            args.push(format!("--{}", name));
+            args.extend(value);
+        };
+        if !self.day.is_empty() {
+            push("day", Some(self.day.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(",")));
+        }
+        if let Some(part) = self.part {
+            push("part", Some(part.number().to_string()));
+        }
+        if let Some(dataset) = &self.dataset {
+            push("dataset", Some(dataset.name()));
+        }
+        if let Some(input) = &self.input {
+            push("input", Some(input.clone()));
+        }
+        if let Some(bench) = self.bench {
+            push("bench", Some(bench.to_string()));
+        }
+        if let Some(warmup) = self.warmup {
+            push("warmup", Some(warmup.to_string()));
+        }
+        if let Some(bench_time) = self.bench_time {
+            push("bench-time", Some(bench_time.to_string()));
+        }
+        if let Some(parallel) = self.parallel {
+            push("parallel", Some(parallel.to_string()));
+        }
+        if let Some(timeout) = self.timeout {
+            push("timeout", Some(timeout.to_string()));
+        }
+        if self.debug {
+            push("debug", None);
+        }
+        if let Some(level) = self.log_level {
+            push("log-level", Some(format!("{:?}", level).to_lowercase()));
+        }
+        for (day, level) in &self.log_day {
+            push("log-day", Some(format!("{}={}", day, format!("{:?}", level).to_lowercase())));
+        }
+        if let Some(log_file) = &self.log_file {
+            push("log-file", Some(log_file.clone()));
+        }
+        if self.log_stderr {
+            push("log-stderr", None);
+        }
+        if self.log_timestamps {
+            push("log-timestamps", None);
+        }
+        if self.capture_logs {
+            push("capture-logs", None);
+        }
+        if !self.bench_tags.is_empty() {
+            push("bench-tags", Some(self.bench_tags.join(",")));
+        }
+        if self.save_bench {
+            push("save-bench", None);
+        }
+        if let Some(baseline) = &self.baseline {
+            push("baseline", Some(baseline.clone()));
+        }
+        push("threshold", Some(self.threshold.to_string()));
+        if let Some(path) = &self.report_json {
+            push("report-json", Some(path.clone()));
+        }
+        if let Some(path) = &self.report_junit {
+            push("report-junit", Some(path.clone()));
+        }
+        if self.record {
+            push("record", None);
+        }
+        args
+    }
+
+    pub fn to_run_option<'a>(&self, days_restriction: DaysRestriction<'a>) -> RunOption<'a> {
+        let mut options = RunOption::default(days_restriction);
+        if let Some(part) = self.part {
+            options = options.only(part);
+        }
+        if let Some(dataset) = &self.dataset {
+            options = options.only_dataset(dataset.clone());
+        }
+        if let Some(input) = &self.input {
+            options = options.with_input(InputSource::from_arg(input));
+        }
+        if let Some(config) = self.bench_config() {
+            options = options.bench_with(config);
+        }
+        if !self.bench_tags.is_empty() {
+            let bench_tags: Vec<&str> = self.bench_tags.iter().map(|t| t.as_str()).collect();
+            options = options.with_bench_tags(&bench_tags);
+        }
+        if let Some(timeout) = self.timeout {
+            options = options.with_timeout(Duration::from_secs_f64(timeout));
+        }
+        if self.debug {
+            options = options.debug();
+        }
+        if self.record {
+            options = options.record();
+        }
+        options
+    }
+}
+
+fn parse_part(value: &str) -> Result<Part, String> {
+    match value {
+        "1" => Ok(Part::Part1),
+        "2" => Ok(Part::Part2),
+        _ => Err(format!("Unknown part {} (expected 1 or 2)", value)),
+    }
+}
+
+fn parse_dataset(value: &str) -> Result<Dataset, String> {
+    match value.to_lowercase().as_str() {
+        "test" => Ok(Dataset::Test),
+        "real" => Ok(Dataset::Real),
+        other => other
+            .strip_prefix("test_")
+            .filter(|name| !name.is_empty())
+            .map(|name| Dataset::NamedTest(name.to_string()))
+            .ok_or(format!("Unknown dataset {} (expected test, test_<name> or real)", value)),
+    }
+}
+
+fn parse_log_level(value: &str) -> Result<LogLevel, String> {
+    match value.to_lowercase().as_str() {
+        "error" => Ok(LogLevel::ERROR),
+        "info" => Ok(LogLevel::INFO),
+        "debug" => Ok(LogLevel::DEBUG),
+        "trace" => Ok(LogLevel::TRACE),
+        _ => Err(format!("Unknown log level {} (expected error, info, debug or trace)", value)),
+    }
+}
+
+fn parse_day_log_level(value: &str) -> Result<(u8, LogLevel), String> {
+    let (day, level) = value.split_once('=').ok_or(format!("Invalid day log level {} (expected DAY=LEVEL)", value))?;
+    let day = day.parse::<u8>().map_err(|_| format!("Invalid day {}", day))?;
+    Ok((day, parse_log_level(level)?))
+}
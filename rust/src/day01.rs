@@ -1,19 +1,21 @@
-use crate::{
-    check_result,
-    utils::{Context, Part},
-};
+use crate::utils::{Answer, Context, Day, Solution};
 
-fn parse(lines: &Vec<String>) -> Vec<&String> {
-    return lines
-        .iter()
-        .collect();
-}
+pub const DAY: Day = Day::per_part::<Puzzle>(1);
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Vec<String>;
+
+    fn parse(_context: &Context, lines: &[String]) -> Self::Input {
+        return lines.to_vec();
+    }
+
+    fn part1(_context: &Context, _input: &Self::Input) -> Answer {
+        return 0.into();
+    }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let values = parse(lines);
-    if context.is_part(Part::Part1) {
-        check_result!(context, 0, [0, 0]);
-    } else {
-        check_result!(context, 0, [0, 0]);
+    fn part2(_context: &Context, _input: &Self::Input) -> Answer {
+        return 0.into();
     }
 }
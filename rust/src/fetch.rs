@@ -0,0 +1,351 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::utils::{self, Dataset};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+/// Minimal delay between two requests, to be gentle with the server
+pub const MIN_DELAY: Duration = Duration::from_secs(3);
+const USER_AGENT: &str = "advent_of_code_2023 rust runner (input fetcher)";
+
+lazy_static! {
+    static ref LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    /// The input of a day needs the session token
+    NoSession,
+    /// The year of the puzzles is not given, never guessed so that nothing is fetched or submitted for another year
+    NoYear,
+    Http(String, u16),
+    RateLimited(String),
+    Transport(String, String),
+    Io(PathBuf, io::Error),
+    NoExample(u8),
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::NoSession => write!(f, "No session token (set AOC_SESSION or use --session)"),
+            FetchError::NoYear => write!(f, "No year (set AOC_YEAR or use --year)"),
+            FetchError::Http(url, status) => write!(f, "{} answered {}", url, status),
+            FetchError::RateLimited(url) => write!(f, "{} answered 429, too many requests", url),
+            FetchError::Transport(url, e) => write!(f, "Cannot reach {}: {}", url, e),
+            FetchError::Io(path, e) => write!(f, "Cannot write {}: {}", path.display(), e),
+            FetchError::NoExample(day) => write!(f, "No example found in the page of day {}", day),
+        }
+    }
+}
+
+/// Outcome of a fetch: the file was already there, or has just been downloaded
+pub enum Fetched {
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Downloads the inputs and examples into the data directory, with the names read by the runner.
+/// A file already present is never downloaded again
+pub struct Fetcher {
+    base_url: String,
+    session: Option<String>,
+    year: Option<u16>,
+    min_delay: Duration,
+    data_dir: PathBuf,
+}
+
+impl Fetcher {
+    /// The base url can point to a local server standing in for the real one
+    pub fn new(base_url: &str, session: Option<String>) -> Fetcher {
+        Fetcher {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+            year: None,
+            min_delay: MIN_DELAY,
+            data_dir: utils::data_dir(),
+        }
+    }
+
+    pub fn with_min_delay(self, min_delay: Duration) -> Fetcher {
+        Fetcher { min_delay, ..self }
+    }
+
+    pub fn with_year(self, year: u16) -> Fetcher {
+        Fetcher { year: Some(year), ..self }
+    }
+
+    /// Directory of the downloaded files (the data directory by default)
+    #[allow(dead_code)]
+    pub fn with_data_dir(self, data_dir: PathBuf) -> Fetcher {
+        Fetcher { data_dir, ..self }
+    }
+
+    pub fn year(&self) -> Option<u16> {
+        self.year
+    }
+
+    /// Path of a day page (e.g. `/2023/day/5/input`)
+    pub fn day_path(&self, day: u8, page: &str) -> Result<String, FetchError> {
+        let year = self.year.ok_or(FetchError::NoYear)?;
+        Ok(format!("/{}/day/{}{}", year, day, page))
+    }
+
+    /// Waits until the minimal delay since the previous request (of any fetcher) is elapsed
+    fn throttle(&self) {
+        let mut last_request = LAST_REQUEST.lock().unwrap();
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_delay {
+                thread::sleep(self.min_delay - elapsed);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    fn get(&self, path: &str, with_session: bool) -> Result<String, FetchError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = ureq::get(&url).set("User-Agent", USER_AGENT);
+        if with_session {
+            let session = self.session.as_ref().ok_or(FetchError::NoSession)?;
+            request = request.set("Cookie", &format!("session={}", session));
+        }
+        self.throttle();
+        Fetcher::read_response(url.clone(), request.call())
+    }
+
+    /// Posts a form (e.g. an answer), with the session token
+    pub fn post(&self, path: &str, form: &[(&str, String)]) -> Result<String, FetchError> {
+        let url = format!("{}{}", self.base_url, path);
+        let session = self.session.as_ref().ok_or(FetchError::NoSession)?;
+        let request = ureq::post(&url)
+            .set("User-Agent", USER_AGENT)
+            .set("Cookie", &format!("session={}", session));
+        let form: Vec<(&str, &str)> = form.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.throttle();
+        Fetcher::read_response(url.clone(), request.send_form(&form))
+    }
+
+    fn read_response(url: String, response: Result<ureq::Response, ureq::Error>) -> Result<String, FetchError> {
+        match response {
+            Ok(response) => response.into_string().map_err(|e| FetchError::Transport(url, e.to_string())),
+            Err(ureq::Error::Status(429, _)) => Err(FetchError::RateLimited(url)),
+            Err(ureq::Error::Status(status, _)) => Err(FetchError::Http(url, status)),
+            Err(e) => Err(FetchError::Transport(url, e.to_string())),
+        }
+    }
+
+    /// Downloads the file unless already there. An empty file (e.g. created by `new-day`) counts as missing
+    fn fetch_to(&self, path: PathBuf, download: impl FnOnce() -> Result<String, FetchError>) -> Result<Fetched, FetchError> {
+        if fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+            return Ok(Fetched::Cached(path));
+        }
+        let content = download()?;
+        let written = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| fs::write(&path, content));
+        match written {
+            Ok(_) => Ok(Fetched::Downloaded(path)),
+            Err(e) => Err(FetchError::Io(path, e)),
+        }
+    }
+
+    /// Real input of the day, in `day_N.dat`
+    pub fn fetch_input(&self, day: u8) -> Result<Fetched, FetchError> {
+        self.fetch_to(self.data_dir.join(utils::data_file_name(&day, &Dataset::Real)), || {
+            self.get(&self.day_path(day, "/input")?, true)
+        })
+    }
+
+    /// First example of the puzzle page, in `day_N_test.dat`
+    pub fn fetch_example(&self, day: u8) -> Result<Fetched, FetchError> {
+        self.fetch_to(self.data_dir.join(utils::data_file_name(&day, &Dataset::Test)), || {
+            let page = self.get(&self.day_path(day, "")?, self.session.is_some())?;
+            extract_example(&page).ok_or(FetchError::NoExample(day))
+        })
+    }
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Content of the first `<pre><code>` block of a puzzle page
+fn extract_example(page: &str) -> Option<String> {
+    let start = page.find("<pre><code>")? + "<pre><code>".len();
+    let end = start + page[start..].find("</code></pre>")?;
+    Some(unescape_html(&strip_tags(&page[start..end])))
+}
+
+/// Local stand-in for the advent of code server, for the tests
+#[cfg(test)]
+pub mod stand_in {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Requests received by the server (head and body)
+    pub type Requests = Arc<Mutex<Vec<String>>>;
+
+    /// Starts a server answering each request with the (status, body) given by `respond` for its path.
+    /// Returns its base url and the received requests
+    pub fn serve(respond: fn(&str) -> (u16, String)) -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    head.push_str(&line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let path = head.split_whitespace().nth(1).unwrap_or_default().to_string();
+                received.lock().unwrap().push(format!("{}\r\n{}", head, String::from_utf8_lossy(&body)));
+
+                let (status, content) = respond(&path);
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content.len(),
+                    content
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::stand_in::serve;
+    use super::*;
+
+    fn respond(path: &str) -> (u16, String) {
+        match path {
+            "/2023/day/5/input" => (200, "79 14 55 13\n".to_string()),
+            "/2023/day/5" => (200, "<p>For example:</p><pre><code>seeds: <em>79</em> 14\n1 &lt; 2 &amp;&amp; 3 &gt; 2\n</code></pre>".to_string()),
+            "/2023/day/6/input" => (429, "Too many requests".to_string()),
+            _ => (404, "Not found".to_string()),
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc_fetch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn fetcher(url: &str, session: Option<&str>, dir: &std::path::Path) -> Fetcher {
+        Fetcher::new(url, session.map(|s| s.to_string()))
+            .with_year(2023)
+            .with_min_delay(Duration::ZERO)
+            .with_data_dir(dir.to_path_buf())
+    }
+
+    #[test]
+    fn input_is_downloaded_once_with_the_session() {
+        let (url, requests) = serve(respond);
+        let dir = temp_dir("input");
+        let fetcher = fetcher(&url, Some("secret"), &dir);
+
+        let Ok(Fetched::Downloaded(path)) = fetcher.fetch_input(5) else { panic!("input not downloaded") };
+        assert_eq!(path, dir.join("day_5.dat"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "79 14 55 13\n");
+        assert!(requests.lock().unwrap()[0].contains("session=secret"));
+
+        assert!(matches!(fetcher.fetch_input(5), Ok(Fetched::Cached(_))));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn input_needs_a_session() {
+        let (url, requests) = serve(respond);
+        let dir = temp_dir("no_session");
+        assert!(matches!(fetcher(&url, None, &dir).fetch_input(5), Err(FetchError::NoSession)));
+        assert!(requests.lock().unwrap().is_empty());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn example_is_the_first_code_block() {
+        let (url, _) = serve(respond);
+        let dir = temp_dir("example");
+        let Ok(Fetched::Downloaded(path)) = fetcher(&url, None, &dir).fetch_example(5) else { panic!("example not downloaded") };
+        assert_eq!(path, dir.join("day_5_test.dat"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "seeds: 79 14\n1 < 2 && 3 > 2\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extract_example_without_code_block() {
+        assert_eq!(extract_example("<pre><code>a\n</code></pre><pre><code>b</code></pre>"), Some("a\n".to_string()));
+        assert_eq!(extract_example("<p>No example</p>"), None);
+    }
+
+    #[test]
+    fn too_many_requests_is_rate_limited() {
+        let (url, _) = serve(respond);
+        let dir = temp_dir("rate_limited");
+        assert!(matches!(fetcher(&url, Some("secret"), &dir).fetch_input(6), Err(FetchError::RateLimited(_))));
+        assert!(!dir.join("day_6.dat").exists());
+    }
+
+    #[test]
+    fn year_is_required() {
+        let (url, requests) = serve(respond);
+        let dir = temp_dir("no_year");
+        let fetcher = Fetcher::new(&url, Some("secret".to_string())).with_min_delay(Duration::ZERO).with_data_dir(dir.clone());
+        assert_eq!(fetcher.year(), None);
+        assert!(matches!(fetcher.fetch_input(5), Err(FetchError::NoYear)));
+        assert!(matches!(fetcher.fetch_example(5), Err(FetchError::NoYear)));
+        assert!(requests.lock().unwrap().is_empty());
+        assert!(!dir.exists());
+    }
+}
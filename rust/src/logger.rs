@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+
+use crate::utils::LogLevel;
+
+lazy_static! {
+    static ref LOGGER: Mutex<Logger> = Mutex::new(Logger::default());
+}
+
+/// Where the log lines are written
+pub enum Target {
+    Stdout,
+    Stderr,
+    File(File),
+}
+
+pub struct Logger {
+    /// Level of the days without override
+    pub level: LogLevel,
+    /// Level overrides per day number
+    pub day_levels: BTreeMap<u8, LogLevel>,
+    pub target: Target,
+    /// Prefix each line with the UTC time of day
+    pub timestamps: bool,
+    /// Keep the log lines of each run in the report
+    pub capture: bool,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger {
+            level: LogLevel::INFO,
+            day_levels: BTreeMap::new(),
+            target: Target::Stdout,
+            timestamps: false,
+            capture: false,
+        }
+    }
+}
+
+/// Replaces the logger configuration, before the runs start
+pub fn init(logger: Logger) {
+    *LOGGER.lock().unwrap() = logger;
+}
+
+/// Level of the given day, its override if any or the default level
+pub fn day_level(day: u8) -> LogLevel {
+    let logger = LOGGER.lock().unwrap();
+    logger.day_levels.get(&day).copied().unwrap_or(logger.level)
+}
+
+/// Level override of the given day, if any
+pub fn day_override(day: u8) -> Option<LogLevel> {
+    LOGGER.lock().unwrap().day_levels.get(&day).copied()
+}
+
+pub fn is_capture() -> bool {
+    LOGGER.lock().unwrap().capture
+}
+
+fn timestamp() -> String {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let seconds = millis / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03} ",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+        millis % 1000
+    )
+}
+
+/// Formats a log line as `[time ]LEVEL [prefix] message`, ended by a new line
+pub fn format_line(level: LogLevel, prefix: &str, message: &str) -> String {
+    let timestamp = if LOGGER.lock().unwrap().timestamps { timestamp() } else { String::new() };
+    format!("{}{:<5} [{}] {}\n", timestamp, format!("{:?}", level), prefix, message)
+}
+
+/// Writes already formatted lines to the target
+pub fn write(text: &str) {
+    let mut logger = LOGGER.lock().unwrap();
+    let _ = match &mut logger.target {
+        Target::Stdout => std::io::stdout().lock().write_all(text.as_bytes()),
+        Target::Stderr => std::io::stderr().lock().write_all(text.as_bytes()),
+        Target::File(file) => file.write_all(text.as_bytes()),
+    };
+}
+
+/// Logs a line that doesn't belong to a run (e.g. `[Day 5]` headers)
+pub fn log(day: u8, level: LogLevel, prefix: &str, message: &str) {
+    if level <= day_level(day) {
+        write(&format_line(level, prefix, message));
+    }
+}
+
+/// Blank line between the runs of a day
+pub fn separator(day: u8) {
+    if LogLevel::INFO <= day_level(day) {
+        write("\n");
+    }
+}
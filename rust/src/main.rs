@@ -1,60 +1,180 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
 use std::time::Instant;
 
-#[allow(unused_imports)]
-use crate::utils::Part::{Part1, Part2};
-use crate::utils::{Context, DaysRestriction, RunOption};
+use clap::Parser;
+
+use crate::cli::{Cli, Command, FetchArgs, RunArgs, SubmitArgs};
+use crate::fetch::{FetchError, Fetched};
+use crate::utils::{Answer, Context, Dataset, DaysRestriction};
 
+mod answers;
+mod bench;
+mod cli;
+mod fetch;
+mod logger;
 mod map2d;
+mod parallel;
 mod priority_queue;
+mod report;
+mod scaffold;
+mod search;
+mod submit;
 mod utils;
+mod watch;
 
-mod day01;
-/*mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day14;
-mod day16;
-mod day19;
-mod day20;
-mod day23;
-mod day24;*/
+register_days!(day01);
 
 fn main() {
+    let cli = Cli::parse();
+    match cli.command.unwrap_or_default() {
+        Command::Run(args) => {
+            match args.logger() {
+                Ok(logger) => logger::init(logger),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+            if run(&args) > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::List => list(),
+        Command::Watch(args) => watch::watch(&args),
+        Command::Fetch(args) => {
+            if fetch(&args) > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Submit(args) => {
+            if !submit(&args) {
+                std::process::exit(1);
+            }
+        }
+        Command::NewDay { day } => match scaffold::new_day(day) {
+            Ok(files) => {
+                for file in files {
+                    println!("[NEW] {}", file);
+                }
+            }
+            Err(e) => {
+                println!("[NEW] {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Submits the answer of a part and returns whether it is correct
+fn submit(args: &SubmitArgs) -> bool {
+    let fetcher = args.server.fetcher();
+    let Some(year) = fetcher.year() else {
+        println!("[SUBMIT] {}", FetchError::NoYear);
+        return false;
+    };
+    println!("[SUBMIT] Puzzles of {}", year);
+    let answer = match &args.answer {
+        Some(answer) => Answer::from(answer.as_str()),
+        None => {
+            let Some(day) = all_days().into_iter().find(|d| d.day == args.day) else {
+                println!("[SUBMIT] Day {} is not registered", args.day);
+                return false;
+            };
+            match utils::read_lines(&day.day, day.file_part(args.part), &Dataset::Real) {
+                Ok(lines) => {
+                    let answer = day.solve_lines(args.part, &lines);
+                    println!("[SUBMIT] Day {} {:?} computed {}", args.day, args.part, answer);
+                    answer
+                }
+                Err(e) => {
+                    println!("[SUBMIT] Day {} {:?} cannot be computed: {}", args.day, args.part, e);
+                    return false;
+                }
+            }
+        }
+    };
+    match submit::submit(&fetcher, &submit::history_file(), &answers::answers_file(), args.day, args.part, &answer) {
+        Ok(verdict) => {
+            println!("[SUBMIT] Day {} {:?} answer {}: {:?}", args.day, args.part, answer, verdict);
+            verdict == submit::Verdict::Correct
+        }
+        Err(e) => {
+            println!("[SUBMIT] Day {} {:?} answer {}: {}", args.day, args.part, answer, e);
+            false
+        }
+    }
+}
+
+/// Fetches the inputs (and examples) of the selected days and returns the number of errors
+fn fetch(args: &FetchArgs) -> usize {
+    let fetcher = args.server.fetcher();
+    let Some(year) = fetcher.year() else {
+        println!("[FETCH] {}", FetchError::NoYear);
+        return 1;
+    };
+    println!("[FETCH] Puzzles of {}", year);
+    let days: Vec<u8> = if args.day.is_empty() { all_days().iter().map(|d| d.day).collect() } else { args.day.clone() };
+    let mut nb_errors = 0;
+    for day in days {
+        let mut results = vec![("input", fetcher.fetch_input(day))];
+        if !args.no_examples {
+            results.push(("example", fetcher.fetch_example(day)));
+        }
+        for (kind, result) in results {
+            match result {
+                Ok(Fetched::Cached(path)) => println!("[FETCH] Day {} {} already in {}", day, kind, path.display()),
+                Ok(Fetched::Downloaded(path)) => println!("[FETCH] Day {} {} downloaded in {}", day, kind, path.display()),
+                Err(e) => {
+                    nb_errors += 1;
+                    println!("[FETCH] Day {} {} failed: {}", day, kind, e);
+                }
+            }
+        }
+    }
+    nb_errors
+}
+
+fn list() {
+    for day in all_days() {
+        println!("Day {:>2} {:?}", day.day, day.kind());
+    }
+}
+
+/// Runs the selected days and returns the number of errors
+fn run(args: &RunArgs) -> usize {
     let start = Instant::now();
-    //let days_restriction:DaysRestriction = &Some(vec![17]);
-    let days_restriction: DaysRestriction = &None;
-
-    utils::run_all(&1, &day01::puzzle, RunOption::default(days_restriction));
-    /*utils::run_all(&2, &day02::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&3, &day03::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&4, &day04::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&5, &day05::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&6, &day06::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&7, &day07::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&8, &day08::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&9, &day09::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&10, &day10::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&11, &day11::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&12, &day12::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&13, &day13::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&14, &day14::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&15, &day15::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&16, &day16::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&17, &day17::puzzle, RunOption::default(days_restriction));*/
+    let days_restriction: DaysRestriction = &args.days_restriction();
+
+    match args.parallel {
+        Some(nb_threads) => {
+            let options = args.to_run_option(days_restriction);
+            let jobs = all_days().iter().flat_map(|day| day.jobs(&options)).collect();
+            parallel::run_jobs(jobs, nb_threads);
+        }
+        None => {
+            for day in all_days() {
+                day.run(args.to_run_option(days_restriction));
+            }
+        }
+    }
     let duration = start.elapsed().as_millis() as u64;
-    println!("");
+    println!();
+    if args.save_bench {
+        bench::save(&bench::current_revision());
+    }
+    if let Some(baseline) = &args.baseline {
+        let revision = if baseline == "HEAD" { bench::head_revision() } else { baseline.clone() };
+        let nb_regressions = bench::compare(&revision, args.threshold);
+        println!("[BENCH] {} regressions", nb_regressions);
+    }
+    if let Some(path) = &args.report_json {
+        report::write_json(path);
+    }
+    if let Some(path) = &args.report_junit {
+        report::write_junit(path);
+    }
+    report::print_summary();
     println!("[ALL] Overall finished in {} ms with {} errors", duration, Context::get_errors());
+    Context::get_errors()
 }
@@ -1,7 +1,11 @@
 #![allow(unused)]
 
+use std::collections::BTreeMap;
+use std::fmt::Display;
 use std::ops::{Add, RangeInclusive, Sub};
 
+use rustc_hash::FxHashMap;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Pos {
     pub x: usize,
@@ -45,14 +49,14 @@ impl Pos {
     pub fn move_multiple_pos(&self, dirs: &[Direction], height: usize, width: usize) -> Option<Pos> {
         let mut curr_pos = Pos { x: self.x, y: self.y };
         for dir in dirs {
-            if let Some(new_pos) = self.move_pos(dir, height, width) {
+            if let Some(new_pos) = curr_pos.move_pos(dir, height, width) {
                 curr_pos.x = new_pos.x;
                 curr_pos.y = new_pos.y;
             } else {
                 return None;
             }
         }
-        return None;
+        return Some(curr_pos);
     }
 
     pub fn move_pos_anydir(&self, dir_any: &DirectionAny, height: usize, width: usize) -> Option<Pos> {
@@ -97,6 +101,16 @@ impl Direction {
         }
     }
 
+    /// Character drawing the direction
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::UP => '^',
+            Direction::DOWN => 'v',
+            Direction::LEFT => '<',
+            Direction::RIGHT => '>',
+        }
+    }
+
     pub fn turn_counterclockwise(&self) -> &Direction {
         match self {
             Direction::UP => &Direction::LEFT,
@@ -191,23 +205,115 @@ impl Vec2D {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Map2DError {
+    /// No row (or only empty lines)
+    Empty,
+    /// The row (numbered from 0) doesn't have the width of the first one
+    Ragged { row: usize, width: usize, expected: usize },
+}
+
+impl Display for Map2DError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Map2DError::Empty => write!(f, "Empty map"),
+            Map2DError::Ragged { row, width, expected } => {
+                write!(f, "Row {} has {} cells instead of {} (the width of the first row)", row, width, expected)
+            }
+        }
+    }
+}
+
+/// Positions of each marker character found while parsing a map
+pub type Markers = BTreeMap<char, Vec<Pos>>;
+
+/// Grid stored row after row in a single `Vec`, the cell (x, y) being at index `y * width + x`
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Map2D<T> {
-    content: Vec<Vec<T>>,
+    content: Vec<T>,
     width: usize,
     height: usize,
 }
 
 impl<T> Map2D<T> {
+    /// Builds the grid from its rows, that must all have the same width (panics otherwise, see `try_new`)
     pub fn new(content: Vec<Vec<T>>) -> Map2D<T> {
+        return Map2D::try_new(content).unwrap_or_else(|e| panic!("Invalid Map2D: {}", e));
+    }
+
+    /// Builds the grid from its rows, failing if a row doesn't have the width of the first one.
+    /// No row gives an empty map
+    pub fn try_new(content: Vec<Vec<T>>) -> Result<Map2D<T>, Map2DError> {
+        let height = content.len();
+        let width = content.first().map(|row| row.len()).unwrap_or(0);
+        if let Some((row, cells)) = content.iter().enumerate().find(|(_, cells)| cells.len() != width) {
+            return Err(Map2DError::Ragged {
+                row,
+                width: cells.len(),
+                expected: width,
+            });
+        }
+        return Ok(Map2D {
+            content: content.into_iter().flatten().collect(),
+            width,
+            height,
+        });
+    }
+
+    /// Parses the lines of a puzzle, one cell per character. The trailing empty lines are ignored
+    pub fn from_lines<S: AsRef<str>>(lines: &[S], fct: impl Fn(char) -> T) -> Result<Map2D<T>, Map2DError> {
+        return Map2D::from_lines_with_positions(lines, &[], fct).map(|(map, _)| map);
+    }
+
+    /// Parses the lines of a puzzle like `from_lines`, also returning the positions of the marker characters (e.g. `S` and `E`).
+    /// The markers are mapped by `fct` like any other character
+    pub fn from_lines_with_positions<S: AsRef<str>>(
+        lines: &[S],
+        markers: &[char],
+        fct: impl Fn(char) -> T,
+    ) -> Result<(Map2D<T>, Markers), Map2DError> {
+        let nb_lines = lines.len() - lines.iter().rev().take_while(|l| l.as_ref().is_empty()).count();
+        if nb_lines == 0 {
+            return Err(Map2DError::Empty);
+        }
+        let mut positions: Markers = markers.iter().map(|c| (*c, vec![])).collect();
+        let mut rows = Vec::with_capacity(nb_lines);
+        for (y, line) in lines[..nb_lines].iter().enumerate() {
+            let row: Vec<T> = line
+                .as_ref()
+                .chars()
+                .enumerate()
+                .map(|(x, c)| {
+                    if let Some(found) = positions.get_mut(&c) {
+                        found.push(Pos { x, y });
+                    }
+                    fct(c)
+                })
+                .collect();
+            rows.push(row);
+        }
+        return Map2D::try_new(rows).map(|map| (map, positions));
+    }
+
+    /// Builds the grid from its cells, row after row
+    pub fn from_vec(content: Vec<T>, width: usize, height: usize) -> Map2D<T> {
+        assert_eq!(content.len(), width * height, "A Map2D of {}x{} needs {} cells", width, height, width * height);
+        return Map2D { content, width, height };
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Map2D<T>
+    where
+        T: Clone,
+    {
         return Map2D {
-            height: content.len(),
-            width: content[0].len(),
-            content: content,
+            content: vec![value; width * height],
+            width,
+            height,
         };
     }
 
-    pub fn get_content(&self)->&Vec<Vec<T>>{
+    /// All the cells, row after row
+    pub fn get_content(&self) -> &[T] {
         return &self.content;
     }
 
@@ -219,6 +325,48 @@ impl<T> Map2D<T> {
         return self.width;
     }
 
+    /// Index of the position in the content
+    pub fn index(&self, pos: &Pos) -> usize {
+        // Checked here since an x outside of the map would silently be a cell of another row
+        assert!(self.is_valid_pos(pos), "{:?} is outside of the {}x{} map", pos, self.width, self.height);
+        return pos.y * self.width + pos.x;
+    }
+
+    /// Position of an index of the content
+    pub fn pos(&self, index: usize) -> Pos {
+        return Pos {
+            x: index % self.width,
+            y: index / self.width,
+        };
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        return &self.content[y * self.width..(y + 1) * self.width];
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        return &mut self.content[y * self.width..(y + 1) * self.width];
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        return self.content.chunks(self.width.max(1));
+    }
+
+    /// Cells of the column, from top to bottom
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} is outside of the {}x{} map", x, self.width, self.height);
+        return self.content[x..].iter().step_by(self.width);
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        return (0..self.width).map(|x| self.column(x));
+    }
+
+    /// Cells with their position, row after row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        return self.content.iter().enumerate().map(|(index, v)| (self.pos(index), v));
+    }
+
     pub fn move_pos(&self, pos: &Pos, dir: &Direction) -> Option<Pos> {
         pos.move_pos(dir, self.height, self.width)
     }
@@ -231,8 +379,20 @@ impl<T> Map2D<T> {
         pos.move_multiple_pos(dir, self.height, self.width)
     }
 
+    /// Positions next to the given one (up, right, down, left), inside the map
+    pub fn neighbours(&self, pos: &Pos) -> impl Iterator<Item = Pos> + '_ {
+        let pos = *pos;
+        return Direction::ALL_DIRECTIONS_CLOCKWISE.iter().filter_map(move |dir| self.move_pos(&pos, dir));
+    }
+
+    /// Positions next to the given one, diagonals included, inside the map
+    pub fn neighbours_anydir(&self, pos: &Pos) -> impl Iterator<Item = Pos> + '_ {
+        let pos = *pos;
+        return DirectionAny::ALL_DIRECTIONS_CLOCKWISE.iter().filter_map(move |dir| self.move_pos_anydir(&pos, dir));
+    }
+
     pub fn is_valid_pos(&self, pos: &Pos) -> bool {
-        pos.x < self.width && pos.y < self.width
+        pos.x < self.width && pos.y < self.height
     }
 
     pub fn is_border(&self, pos: &Pos) -> bool {
@@ -272,15 +432,17 @@ impl<T> Map2D<T> {
     }
 
     pub fn get(&self, pos: &Pos) -> &T {
-        return &self.content[pos.y][pos.x];
+        return &self.content[self.index(pos)];
     }
 
     pub fn get_mut(&mut self, pos: &Pos) -> &mut T {
-        return &mut self.content[pos.y][pos.x];
+        let index = self.index(pos);
+        return &mut self.content[index];
     }
 
     pub fn set(&mut self, pos: &Pos, new_v: T) {
-        self.content[pos.y][pos.x] = new_v;
+        let index = self.index(pos);
+        self.content[index] = new_v;
     }
 
     pub fn iter_dir(&self, pos: Pos, dir: Direction, start_at_current: bool) -> IterDir {
@@ -328,6 +490,111 @@ impl<T> Map2D<T> {
     }
 }
 
+impl<T: Display> Display for Map2D<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn ansi_code(&self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+        }
+    }
+}
+
+/// Rendering of a map, one character per cell, with overlays drawn over the cells (the last added on top).
+/// Displays as text, e.g. `log!(debug, context, "{}", map.render(|c| *c).path(&path))`, or with `Context::debug_block`
+pub struct MapRender<'a, T> {
+    map: &'a Map2D<T>,
+    cell: Box<dyn Fn(&T) -> char + 'a>,
+    overlays: FxHashMap<Pos, (char, Color)>,
+    colored: bool,
+}
+
+impl<T> Map2D<T> {
+    pub fn render<'a>(&'a self, cell: impl Fn(&T) -> char + 'a) -> MapRender<'a, T> {
+        return MapRender {
+            map: self,
+            cell: Box::new(cell),
+            overlays: FxHashMap::default(),
+            colored: false,
+        };
+    }
+}
+
+impl<'a, T> MapRender<'a, T> {
+    /// Draws the cells with the given character and color (only shown with `colored`)
+    pub fn mark_with<'p>(mut self, cells: impl IntoIterator<Item = &'p Pos>, c: char, color: Color) -> MapRender<'a, T> {
+        for pos in cells {
+            self.overlays.insert(*pos, (c, color));
+        }
+        return self;
+    }
+
+    /// Marks the cells with `#`
+    pub fn mark<'p>(self, cells: impl IntoIterator<Item = &'p Pos>) -> MapRender<'a, T> {
+        return self.mark_with(cells, '#', Color::Red);
+    }
+
+    /// Highlights the cells of a path with `O`
+    pub fn path(self, path: &[Pos]) -> MapRender<'a, T> {
+        return self.mark_with(path, 'O', Color::Green);
+    }
+
+    /// Draws an arrow for each direction
+    pub fn arrows<'p>(mut self, directions: impl IntoIterator<Item = &'p (Pos, Direction)>) -> MapRender<'a, T> {
+        for (pos, dir) in directions {
+            self.overlays.insert(*pos, (dir.arrow(), Color::Cyan));
+        }
+        return self;
+    }
+
+    /// Draws the overlays with ANSI colors
+    pub fn colored(self) -> MapRender<'a, T> {
+        return MapRender { colored: true, ..self };
+    }
+}
+
+impl<'a, T> Display for MapRender<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, value) in self.map.get_content().iter().enumerate() {
+            let pos = self.map.pos(index);
+            match self.overlays.get(&pos) {
+                Some((c, color)) if self.colored => write!(f, "\x1b[{}m{}\x1b[0m", color.ansi_code(), c)?,
+                Some((c, _)) => write!(f, "{}", c)?,
+                None => write!(f, "{}", (self.cell)(value))?,
+            }
+            if pos.x == self.map.width - 1 {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct IterDir {
     start_at_current: bool,
     pos: Pos,
@@ -421,7 +688,7 @@ impl IterAll {
 
     fn next_y(&self) -> Option<usize> {
         if let Some(new_y) = self.pos.y.checked_add_signed(self.delta_y) {
-            if (new_y < self.width) {
+            if (new_y < self.height) {
                 return Some(new_y);
             }
         }
@@ -462,3 +729,180 @@ impl Iterator for IterAll {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3 columns and 2 rows, so that a mix-up of the width and the height shows
+    fn wide_map() -> Map2D<u8> {
+        return Map2D::from_vec(vec![0, 1, 2, 3, 4, 5], 3, 2);
+    }
+
+    fn positions(coords: &[(usize, usize)]) -> Vec<Pos> {
+        return coords.iter().map(|&(x, y)| Pos { x, y }).collect();
+    }
+
+    #[test]
+    fn index_and_pos_of_a_non_square_map() {
+        let map = wide_map();
+        assert_eq!((map.width(), map.height()), (3, 2));
+        assert_eq!(map.index(&Pos { x: 2, y: 0 }), 2);
+        assert_eq!(map.index(&Pos { x: 1, y: 1 }), 4);
+        assert_eq!(map.pos(2), Pos { x: 2, y: 0 });
+        assert_eq!(map.pos(3), Pos { x: 0, y: 1 });
+        assert_eq!(*map.get(&Pos { x: 2, y: 1 }), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 3x2 map")]
+    fn index_outside_of_the_row() {
+        wide_map().index(&Pos { x: 3, y: 0 });
+    }
+
+    #[test]
+    fn is_valid_pos_of_a_non_square_map() {
+        let map = wide_map();
+        assert!(map.is_valid_pos(&Pos { x: 2, y: 1 }));
+        assert!(!map.is_valid_pos(&Pos { x: 3, y: 0 }));
+        assert!(!map.is_valid_pos(&Pos { x: 0, y: 2 }));
+        assert_eq!(map.get_opt(&Pos { x: 1, y: 2 }), None);
+    }
+
+    #[test]
+    fn rows_of_a_non_square_map() {
+        let mut map = wide_map();
+        assert_eq!(map.row(1), &[3, 4, 5]);
+        map.row_mut(0)[2] = 20;
+        assert_eq!(map.rows().collect::<Vec<_>>(), vec![&[0, 1, 20][..], &[3, 4, 5][..]]);
+    }
+
+    #[test]
+    fn columns_of_a_non_square_map() {
+        let map = wide_map();
+        assert_eq!(map.column(2).copied().collect::<Vec<_>>(), vec![2, 5]);
+        let columns: Vec<Vec<u8>> = map.columns().map(|column| column.copied().collect()).collect();
+        assert_eq!(columns, vec![vec![0, 3], vec![1, 4], vec![2, 5]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 3 is outside of the 3x2 map")]
+    fn column_outside_of_the_map() {
+        wide_map().column(3);
+    }
+
+    #[test]
+    fn iter_gives_the_cells_row_after_row() {
+        let cells: Vec<(Pos, u8)> = wide_map().iter().map(|(pos, v)| (pos, *v)).collect();
+        let expected: Vec<(Pos, u8)> = positions(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]).into_iter().zip(0..).collect();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn from_vec_and_filled() {
+        let filled = Map2D::filled(3, 2, 7u8);
+        assert_eq!((filled.width(), filled.height()), (3, 2));
+        assert_eq!(filled.get_content(), &[7; 6]);
+        assert_eq!(wide_map(), Map2D::new(vec![vec![0, 1, 2], vec![3, 4, 5]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "A Map2D of 3x2 needs 6 cells")]
+    fn from_vec_with_the_wrong_number_of_cells() {
+        Map2D::from_vec(vec![0u8; 5], 3, 2);
+    }
+
+    #[test]
+    fn iter_all_of_a_non_square_map() {
+        let map = wide_map();
+        let rows_first = positions(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        assert_eq!(map.iter_all_fast().collect::<Vec<_>>(), rows_first);
+        let columns_first = positions(&[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+        assert_eq!(map.iter_all(&[&Direction::DOWN, &Direction::RIGHT]).collect::<Vec<_>>(), columns_first);
+        let backward = positions(&[(2, 1), (2, 0), (1, 1), (1, 0), (0, 1), (0, 0)]);
+        assert_eq!(map.iter_all(&[&Direction::UP, &Direction::LEFT]).collect::<Vec<_>>(), backward);
+        let bottom_up = positions(&[(0, 1), (1, 1), (2, 1), (0, 0), (1, 0), (2, 0)]);
+        assert_eq!(map.iter_all(&[&Direction::RIGHT, &Direction::UP]).collect::<Vec<_>>(), bottom_up);
+    }
+
+    #[test]
+    fn ragged_rows_are_refused() {
+        let error = Map2D::try_new(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8]]).unwrap_err();
+        assert_eq!(error, Map2DError::Ragged { row: 2, width: 2, expected: 3 });
+        assert_eq!(error.to_string(), "Row 2 has 2 cells instead of 3 (the width of the first row)");
+        let error = Map2D::from_lines(&["...", "....", "..."], |c| c).unwrap_err();
+        assert_eq!(error, Map2DError::Ragged { row: 1, width: 4, expected: 3 });
+    }
+
+    #[test]
+    fn no_row_is_an_empty_map() {
+        assert_eq!(Map2D::from_lines::<&str>(&[], |c| c), Err(Map2DError::Empty));
+        assert_eq!(Map2D::from_lines(&["", ""], |c| c), Err(Map2DError::Empty));
+        let map = Map2D::<u8>::try_new(vec![]).unwrap();
+        assert_eq!((map.width(), map.height()), (0, 0));
+    }
+
+    #[test]
+    fn trailing_blank_lines_are_trimmed() {
+        let lines = ["S.#", "..E", "", ""];
+        let (map, markers) = Map2D::from_lines_with_positions(&lines, &['S', 'E', 'X'], |c| c == '#').unwrap();
+        assert_eq!((map.width(), map.height()), (3, 2));
+        assert!(*map.get(&Pos { x: 2, y: 0 }));
+        assert_eq!(markers[&'S'], vec![Pos { x: 0, y: 0 }]);
+        assert_eq!(markers[&'E'], vec![Pos { x: 2, y: 1 }]);
+        assert!(markers[&'X'].is_empty());
+        // A blank line inside the map is still a (ragged) row
+        let error = Map2D::from_lines(&["...", "", "..."], |c| c).unwrap_err();
+        assert_eq!(error, Map2DError::Ragged { row: 1, width: 0, expected: 3 });
+    }
+
+    #[test]
+    fn render_draws_a_line_per_row() {
+        let map = wide_map();
+        assert_eq!(map.render(|v| char::from(b'0' + v)).to_string(), "012\n345\n");
+    }
+
+    #[test]
+    fn render_draws_the_last_overlay_on_top() {
+        let map = Map2D::filled(3, 2, '.');
+        let path = positions(&[(0, 0), (1, 0), (1, 1)]);
+        let render = map.render(|c| *c).mark(&positions(&[(1, 0), (2, 1)])).path(&path).arrows(&[(Pos { x: 1, y: 1 }, Direction::RIGHT)]);
+        let text = render.to_string();
+        assert_eq!(text, "OO.\n.>#\n");
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn render_uses_ansi_codes_only_when_colored() {
+        let map = Map2D::filled(2, 1, '.');
+        let marked = [Pos { x: 1, y: 0 }];
+        assert_eq!(map.render(|c| *c).mark(&marked).to_string(), ".#\n");
+        assert_eq!(map.render(|c| *c).mark(&marked).colored().to_string(), ".\x1b[31m#\x1b[0m\n");
+    }
+
+    #[test]
+    fn neighbours_stay_inside_the_map() {
+        let map = Map2D::filled(4, 3, 0u8);
+        assert_eq!(map.neighbours(&Pos { x: 1, y: 1 }).count(), 4);
+        assert_eq!(map.neighbours(&Pos { x: 0, y: 0 }).collect::<Vec<_>>(), positions(&[(1, 0), (0, 1)]));
+    }
+
+    #[test]
+    fn neighbours_anydir_include_the_diagonals() {
+        let map = Map2D::filled(4, 3, 0u8);
+        let mut inner: Vec<Pos> = map.neighbours_anydir(&Pos { x: 1, y: 1 }).collect();
+        inner.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(inner, positions(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]));
+        let mut top_edge: Vec<Pos> = map.neighbours_anydir(&Pos { x: 2, y: 0 }).collect();
+        top_edge.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(top_edge, positions(&[(1, 0), (3, 0), (1, 1), (2, 1), (3, 1)]));
+        assert_eq!(map.neighbours_anydir(&Pos { x: 3, y: 2 }).count(), 3);
+    }
+
+    #[test]
+    fn move_multiple_pos_chains_the_moves() {
+        let map = Map2D::filled(4, 3, 0u8);
+        assert_eq!(map.move_multiple_pos(&Pos { x: 1, y: 1 }, &[Direction::UP, Direction::RIGHT]), Some(Pos { x: 2, y: 0 }));
+        assert_eq!(map.move_multiple_pos(&Pos { x: 1, y: 0 }, &[Direction::UP, Direction::RIGHT]), None);
+    }
+}
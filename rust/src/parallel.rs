@@ -0,0 +1,28 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::logger;
+use crate::utils::Job;
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Runs the jobs over a pool of threads (0 for the number of CPUs), printing the logs of each job at once when it ends
+pub fn run_jobs(jobs: Vec<Job>, nb_threads: usize) {
+    let nb_threads = if nb_threads == 0 { default_threads() } else { nb_threads };
+    println!("[ALL] {} runs on {} threads", jobs.len(), nb_threads);
+    let queue = Mutex::new(VecDeque::from(jobs));
+    thread::scope(|scope| {
+        for _ in 0..nb_threads {
+            scope.spawn(|| loop {
+                let Some(job) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+                let output = job.execute(true);
+                logger::write(&format!("{}\n", output));
+            });
+        }
+    });
+}
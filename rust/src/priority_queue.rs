@@ -36,7 +36,7 @@ impl<C: Ord + PartialOrd, K, T> Ord for Wrapper<C, K, T> {
 
 impl<C: Ord + PartialOrd, K, T> PartialOrd for Wrapper<C, K, T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.cost.partial_cmp(&self.cost)
+        Some(self.cmp(other))
     }
 }
 
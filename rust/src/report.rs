@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref RESULTS: Mutex<Vec<RunResult>> = Mutex::new(vec![]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Status {
+    Ok,
+    Ko,
+    /// No expected answer known for the run
+    Unchecked,
+    /// No expected answer known, the answer has been recorded
+    Recorded,
+    /// The run panicked
+    Failed,
+    /// The run didn't end before the timeout
+    Timeout,
+}
+
+/// Result of a day/part/dataset run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    pub day: u8,
+    pub part: u8,
+    pub dataset: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench_tag: Option<String>,
+    pub answer: String,
+    pub expected: Option<String>,
+    pub status: Status,
+    pub read_ms: f64,
+    pub parse_ms: f64,
+    pub solve_ms: f64,
+    /// Why the run failed (panic message or timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Log lines of the run, when captured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+}
+
+impl RunResult {
+    fn total_ms(&self) -> f64 {
+        self.read_ms + self.parse_ms + self.solve_ms
+    }
+
+    fn sort_key(&self) -> (u8, u8, u8, &str, Option<&str>) {
+        let dataset_rank = match self.dataset.as_str() {
+            "test" => 0,
+            name if name.starts_with("test_") => 1,
+            "real" => 2,
+            _ => 3,
+        };
+        (self.day, self.part, dataset_rank, &self.dataset, self.bench_tag.as_deref())
+    }
+
+    fn name(&self) -> String {
+        let tag = self.bench_tag.as_ref().map(|t| format!("#{}", t)).unwrap_or_default();
+        format!("part{}/{}{}", self.part, self.dataset, tag)
+    }
+}
+
+pub fn add(result: RunResult) {
+    RESULTS.lock().unwrap().push(result);
+}
+
+/// Whether a result is already recorded for the day/part/dataset and bench variant
+pub fn contains(day: u8, part: u8, dataset: &str, bench_tag: Option<&str>) -> bool {
+    RESULTS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|r| r.day == day && r.part == part && r.dataset == dataset && r.bench_tag.as_deref() == bench_tag)
+}
+
+/// Results ordered by day, part, dataset (as in a sequential run: test, named tests, then real) and bench tag,
+/// since the runs of parallel mode end in any order
+pub fn results() -> Vec<RunResult> {
+    let mut results = RESULTS.lock().unwrap().clone();
+    results.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    results
+}
+
+/// Prints a table of the runs with their status and timings
+pub fn print_summary() {
+    let results = results();
+    if results.is_empty() {
+        return;
+    }
+    println!(
+        "[SUMMARY] {:>3} {:>4} {:<16} {:<10} {:>10} {:>11} {:>11}",
+        "Day", "Part", "Dataset", "Status", "Read (ms)", "Parse (ms)", "Solve (ms)"
+    );
+    for result in &results {
+        let dataset = match &result.bench_tag {
+            Some(tag) => format!("{}#{}", result.dataset, tag),
+            None => result.dataset.clone(),
+        };
+        println!(
+            "[SUMMARY] {:>3} {:>4} {:<16} {:<10} {:>10.2} {:>11.2} {:>11.2}",
+            result.day,
+            result.part,
+            dataset,
+            format!("{:?}", result.status).to_uppercase(),
+            result.read_ms,
+            result.parse_ms,
+            result.solve_ms
+        );
+    }
+    let count = |status: Status| results.iter().filter(|r| r.status == status).count();
+    println!(
+        "[SUMMARY] {} runs: {} OK, {} KO, {} failed, {} timeout, {} unchecked, {} recorded",
+        results.len(),
+        count(Status::Ok),
+        count(Status::Ko),
+        count(Status::Failed),
+        count(Status::Timeout),
+        count(Status::Unchecked),
+        count(Status::Recorded)
+    );
+}
+
+fn write(path: &str, content: String) {
+    match fs::write(path, content) {
+        Ok(_) => println!("[REPORT] Report written in {}", path),
+        Err(e) => println!("[REPORT] Cannot write {}: {}", path, e),
+    }
+}
+
+pub fn write_json(path: &str) {
+    write(path, serde_json::to_string_pretty(&results()).unwrap());
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn write_junit(path: &str) {
+    write(path, junit_xml(&results()));
+}
+
+/// One test suite per day, one test case per part/dataset
+fn junit_xml(results: &[RunResult]) -> String {
+    let mut per_day: BTreeMap<u8, Vec<&RunResult>> = BTreeMap::new();
+    for result in results {
+        per_day.entry(result.day).or_default().push(result);
+    }
+    let nb_failures = results.iter().filter(|r| r.status == Status::Ko).count();
+    let nb_errors = results.iter().filter(|r| matches!(r.status, Status::Failed | Status::Timeout)).count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += &format!(
+        "<testsuites name=\"advent_of_code\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        results.len(),
+        nb_failures,
+        nb_errors
+    );
+    for (day, day_results) in per_day {
+        let suite_failures = day_results.iter().filter(|r| r.status == Status::Ko).count();
+        let suite_errors = day_results.iter().filter(|r| matches!(r.status, Status::Failed | Status::Timeout)).count();
+        let suite_time: f64 = day_results.iter().map(|r| r.total_ms()).sum::<f64>() / 1000.0;
+        xml += &format!(
+            "  <testsuite name=\"day_{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.6}\">\n",
+            day,
+            day_results.len(),
+            suite_failures,
+            suite_errors,
+            suite_time
+        );
+        for result in day_results {
+            xml += &format!(
+                "    <testcase classname=\"day_{}\" name=\"{}\" time=\"{:.6}\">\n",
+                day,
+                escape_xml(&result.name()),
+                result.total_ms() / 1000.0
+            );
+            match result.status {
+                Status::Ko => {
+                    xml += &format!(
+                        "      <failure message=\"{} instead of {}\"/>\n",
+                        escape_xml(&result.answer),
+                        escape_xml(result.expected.as_deref().unwrap_or(""))
+                    )
+                }
+                Status::Failed | Status::Timeout => {
+                    xml += &format!(
+                        "      <error type=\"{:?}\" message=\"{}\"/>\n",
+                        result.status,
+                        escape_xml(result.message.as_deref().unwrap_or(""))
+                    )
+                }
+                Status::Unchecked | Status::Recorded => {
+                    xml += &format!("      <skipped message=\"no expected answer (found {})\"/>\n", escape_xml(&result.answer))
+                }
+                Status::Ok => {}
+            }
+            xml += &format!("      <system-out>{}</system-out>\n", escape_xml(&result.answer));
+            if !result.logs.is_empty() {
+                xml += &format!("      <system-err>{}</system-err>\n", escape_xml(&result.logs.join("\n")));
+            }
+            xml += "    </testcase>\n";
+        }
+        xml += "  </testsuite>\n";
+    }
+    xml += "</testsuites>\n";
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(day: u8, dataset: &str, answer: &str, expected: Option<&str>, status: Status) -> RunResult {
+        RunResult {
+            day,
+            part: 1,
+            dataset: dataset.to_string(),
+            bench_tag: None,
+            answer: answer.to_string(),
+            expected: expected.map(|e| e.to_string()),
+            status,
+            read_ms: 1.0,
+            parse_ms: 0.5,
+            solve_ms: 0.5,
+            message: None,
+            logs: vec![],
+        }
+    }
+
+    #[test]
+    fn junit_of_each_status() {
+        let results = vec![
+            result(1, "test", "142", Some("142"), Status::Ok),
+            result(1, "real", "<a & b>", Some("\"c\""), Status::Ko),
+            RunResult {
+                message: Some("index out of bounds: the len is 3 but the index is 4".to_string()),
+                ..result(2, "test", "", None, Status::Failed)
+            },
+            RunResult {
+                message: Some("No answer after 1 s".to_string()),
+                ..result(2, "real", "", None, Status::Timeout)
+            },
+            RunResult {
+                bench_tag: Some("fast".to_string()),
+                logs: vec!["Day 3 <debug>".to_string(), "done".to_string()],
+                ..result(3, "test_a", "7", None, Status::Unchecked)
+            },
+        ];
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="advent_of_code" tests="5" failures="1" errors="2">
+  <testsuite name="day_1" tests="2" failures="1" errors="0" time="0.004000">
+    <testcase classname="day_1" name="part1/test" time="0.002000">
+      <system-out>142</system-out>
+    </testcase>
+    <testcase classname="day_1" name="part1/real" time="0.002000">
+      <failure message="&lt;a &amp; b&gt; instead of &quot;c&quot;"/>
+      <system-out>&lt;a &amp; b&gt;</system-out>
+    </testcase>
+  </testsuite>
+  <testsuite name="day_2" tests="2" failures="0" errors="2" time="0.004000">
+    <testcase classname="day_2" name="part1/test" time="0.002000">
+      <error type="Failed" message="index out of bounds: the len is 3 but the index is 4"/>
+      <system-out></system-out>
+    </testcase>
+    <testcase classname="day_2" name="part1/real" time="0.002000">
+      <error type="Timeout" message="No answer after 1 s"/>
+      <system-out></system-out>
+    </testcase>
+  </testsuite>
+  <testsuite name="day_3" tests="1" failures="0" errors="0" time="0.002000">
+    <testcase classname="day_3" name="part1/test_a#fast" time="0.002000">
+      <skipped message="no expected answer (found 7)"/>
+      <system-out>7</system-out>
+      <system-err>Day 3 &lt;debug&gt;
+done</system-err>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(junit_xml(&results), expected);
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::{self, Dataset};
+
+/// Day number of the template, replaced by the new day
+const TEMPLATE_DAY: &str = "per_part::<Puzzle>(99)";
+
+fn src_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+fn module_name(day: u8) -> String {
+    format!("day{:02}", day)
+}
+
+/// Template without its copy instructions, for the given day
+fn day_source(template: &str, day: u8) -> Result<String, String> {
+    if !template.contains(TEMPLATE_DAY) {
+        return Err(format!("The template doesn't declare its day as `{}`", TEMPLATE_DAY));
+    }
+    let source: Vec<&str> = template
+        .lines()
+        .filter(|line| !line.starts_with("/// Update the day number after copy"))
+        .collect();
+    Ok(format!("{}\n", source.join("\n")).replace(TEMPLATE_DAY, &format!("per_part::<Puzzle>({})", day)))
+}
+
+/// Adds the module to the `register_days!` list of `main.rs`, keeping the list sorted
+fn register(main: &str, module: &str) -> Result<String, String> {
+    let start = main.find("register_days!(").ok_or("No `register_days!(...)` in main.rs")? + "register_days!(".len();
+    let end = start + main[start..].find(");").ok_or("Unterminated `register_days!(...)` in main.rs")?;
+    let mut modules: Vec<&str> = main[start..end].split(',').map(|m| m.trim()).filter(|m| !m.is_empty()).collect();
+    modules.push(module);
+    modules.sort();
+    modules.dedup();
+
+    let single_line = format!("register_days!({});", modules.join(", "));
+    let list = if single_line.len() <= 150 {
+        modules.join(", ")
+    } else {
+        format!("\n{}\n", modules.iter().map(|m| format!("    {},", m)).collect::<Vec<_>>().join("\n"))
+    };
+    Ok(format!("{}{}{}", &main[..start], list, &main[end..]))
+}
+
+fn create_empty(path: PathBuf, created: &mut Vec<String>) -> Result<(), String> {
+    if !path.exists() {
+        fs::write(&path, "").map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+        created.push(path.display().to_string());
+    }
+    Ok(())
+}
+
+/// Creates `src/dayNN.rs` from `day.temp.rs`, registers it in `main.rs` and creates the empty data files.
+/// Returns the created or updated files
+pub fn new_day(day: u8) -> Result<Vec<String>, String> {
+    new_day_in(&src_dir(), &utils::data_dir(), day)
+}
+
+fn new_day_in(src_dir: &Path, data_dir: &Path, day: u8) -> Result<Vec<String>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("Invalid day {} (expected 1 to 25)", day));
+    }
+    let module = module_name(day);
+    let day_file = src_dir.join(format!("{}.rs", module));
+    if day_file.exists() {
+        return Err(format!("{} already exists", day_file.display()));
+    }
+    let read = |path: &PathBuf| fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e));
+    let write = |path: &PathBuf, content: String| fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e));
+
+    let main_file = src_dir.join("main.rs");
+    let source = day_source(&read(&src_dir.join("day.temp.rs"))?, day)?;
+    let main = register(&read(&main_file)?, &module)?;
+    write(&day_file, source)?;
+    write(&main_file, main)?;
+    let mut created = vec![day_file.display().to_string(), main_file.display().to_string()];
+
+    create_empty(data_dir.join(utils::data_file_name(&day, &Dataset::Real)), &mut created)?;
+    create_empty(data_dir.join(utils::data_file_name(&day, &Dataset::Test)), &mut created)?;
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::fetch::stand_in::serve;
+    use crate::fetch::{Fetched, Fetcher};
+
+    fn respond(path: &str) -> (u16, String) {
+        match path {
+            "/2024/day/3/input" => (200, "xmul(2,4)\n".to_string()),
+            "/2024/day/3" => (200, "<pre><code>mul(1,2)\n</code></pre>".to_string()),
+            _ => (404, "Not found".to_string()),
+        }
+    }
+
+    #[test]
+    fn new_day_then_fetch() {
+        let root = std::env::temp_dir().join(format!("aoc_scaffold_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (src, data) = (root.join("src"), root.join("data"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&data).unwrap();
+        fs::copy(src_dir().join("day.temp.rs"), src.join("day.temp.rs")).unwrap();
+        fs::write(src.join("main.rs"), "mod utils;\nregister_days!(day01);\n").unwrap();
+
+        let created = new_day_in(&src, &data, 3).unwrap();
+        assert_eq!(created.len(), 4);
+        assert!(fs::read_to_string(src.join("day03.rs")).unwrap().contains("per_part::<Puzzle>(3)"));
+        assert_eq!(fs::read_to_string(src.join("main.rs")).unwrap(), "mod utils;\nregister_days!(day01, day03);\n");
+        assert_eq!(fs::read_to_string(data.join("day_3.dat")).unwrap(), "");
+        assert!(new_day_in(&src, &data, 3).is_err());
+
+        // The empty files of the new day don't count as already fetched
+        let (url, requests) = serve(respond);
+        let fetcher = Fetcher::new(&url, Some("secret".to_string()))
+            .with_year(2024)
+            .with_min_delay(Duration::ZERO)
+            .with_data_dir(data.clone());
+        assert!(matches!(fetcher.fetch_input(3), Ok(Fetched::Downloaded(_))));
+        assert!(matches!(fetcher.fetch_example(3), Ok(Fetched::Downloaded(_))));
+        assert_eq!(fs::read_to_string(data.join("day_3.dat")).unwrap(), "xmul(2,4)\n");
+        assert_eq!(fs::read_to_string(data.join("day_3_test.dat")).unwrap(), "mul(1,2)\n");
+        assert!(matches!(fetcher.fetch_input(3), Ok(Fetched::Cached(_))));
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
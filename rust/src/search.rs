@@ -0,0 +1,357 @@
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::ops::Add;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::map2d::{Map2D, Pos};
+use crate::priority_queue::{Cost, Key, PriorityQueue};
+
+/// Best path found by a search, from the start to the goal (both included)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult<S, C> {
+    pub cost: C,
+    pub path: Vec<S>,
+}
+
+/// State queued by Dijkstra and A*, ordered by its priority (the cost, plus the heuristic for A*)
+struct Node<S, C> {
+    state: S,
+    /// Cost from the start
+    distance: C,
+    priority: C,
+}
+
+impl<S, C: Copy> Cost<C> for Node<S, C> {
+    fn cost(&self) -> C {
+        self.priority
+    }
+}
+
+impl<S: Copy, C> Key<S> for Node<S, C> {
+    fn key(&self) -> S {
+        self.state
+    }
+}
+
+/// Path from the start to the state, following the parents
+fn reconstruct_path<S: Copy + Hash + Eq>(parents: &FxHashMap<S, S>, end: S) -> Vec<S> {
+    let mut path = vec![end];
+    let mut current = end;
+    while let Some(parent) = parents.get(&current) {
+        path.push(*parent);
+        current = *parent;
+    }
+    path.reverse();
+    return path;
+}
+
+/// Breadth first search, each move costing 1
+pub fn bfs<S, I>(start: S, mut neighbours: impl FnMut(&S) -> I, mut is_goal: impl FnMut(&S) -> bool) -> Option<SearchResult<S, usize>>
+where
+    S: Copy + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents: FxHashMap<S, S> = FxHashMap::default();
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            let path = reconstruct_path(&parents, state);
+            return Some(SearchResult { cost: path.len() - 1, path });
+        }
+        for next in neighbours(&state) {
+            if next != start && !parents.contains_key(&next) {
+                parents.insert(next, state);
+                queue.push_back(next);
+            }
+        }
+    }
+    return None;
+}
+
+/// Shortest path with weighted moves (the neighbours come with the cost of the move)
+pub fn dijkstra<S, C, I>(start: S, neighbours: impl FnMut(&S) -> I, is_goal: impl FnMut(&S) -> bool) -> Option<SearchResult<S, C>>
+where
+    S: Copy + Hash + Eq,
+    C: Ord + Copy + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    return astar(start, neighbours, |_| C::default(), is_goal);
+}
+
+/// Shortest path guided by a heuristic, that must never overestimate the remaining cost (e.g. `manhattan` on a grid)
+pub fn astar<S, C, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, C>>
+where
+    S: Copy + Hash + Eq,
+    C: Ord + Copy + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut queue = PriorityQueue::new();
+    let mut best: FxHashMap<S, C> = FxHashMap::default();
+    let mut parents: FxHashMap<S, S> = FxHashMap::default();
+    best.insert(start, C::default());
+    queue.push(Node {
+        state: start,
+        distance: C::default(),
+        priority: heuristic(&start),
+    });
+    while let Some(node) = queue.pop() {
+        if is_goal(&node.state) {
+            return Some(SearchResult {
+                cost: node.distance,
+                path: reconstruct_path(&parents, node.state),
+            });
+        }
+        for (next, step) in neighbours(&node.state) {
+            let cost = node.distance + step;
+            if best.get(&next).is_some_and(|c| *c <= cost) {
+                continue;
+            }
+            best.insert(next, cost);
+            parents.insert(next, node.state);
+            queue.push(Node {
+                state: next,
+                distance: cost,
+                priority: cost + heuristic(&next),
+            });
+        }
+    }
+    return None;
+}
+
+/// All the shortest paths found by `dijkstra_all`, as the DAG of the predecessors of each state at its best cost
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<S, C> {
+    pub start: S,
+    /// Best cost to reach a goal
+    pub cost: C,
+    /// Goal states reached at the best cost
+    pub goals: Vec<S>,
+    /// Best cost of each reached state
+    pub costs: FxHashMap<S, C>,
+    predecessors: FxHashMap<S, Vec<S>>,
+}
+
+impl<S: Copy + Hash + Eq, C> ShortestPaths<S, C> {
+    /// States preceding the given one on its shortest paths from the start
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        return self.predecessors.get(state).map(|p| p.as_slice()).unwrap_or_default();
+    }
+
+    /// Number of distinct shortest paths from the start to the goals
+    pub fn count_paths(&self) -> u128 {
+        let mut counts: FxHashMap<S, u128> = FxHashMap::default();
+        counts.insert(self.start, 1);
+        // Depth first on the predecessors, without recursion for the big maps
+        let mut stack: Vec<S> = self.goals.clone();
+        while let Some(state) = stack.last().copied() {
+            if counts.contains_key(&state) {
+                stack.pop();
+                continue;
+            }
+            let missing: Vec<S> = self.predecessors(&state).iter().filter(|p| !counts.contains_key(p)).copied().collect();
+            if missing.is_empty() {
+                let count = self.predecessors(&state).iter().map(|p| counts[p]).sum();
+                counts.insert(state, count);
+                stack.pop();
+            } else {
+                stack.extend(missing);
+            }
+        }
+        return self.goals.iter().map(|goal| counts[goal]).sum();
+    }
+
+    /// States on at least one shortest path, start and goals included
+    pub fn states_on_paths(&self) -> FxHashSet<S> {
+        let mut states: FxHashSet<S> = self.goals.iter().copied().collect();
+        let mut stack: Vec<S> = self.goals.clone();
+        while let Some(state) = stack.pop() {
+            for previous in self.predecessors(&state) {
+                if states.insert(*previous) {
+                    stack.push(*previous);
+                }
+            }
+        }
+        return states;
+    }
+
+    /// Cells on at least one shortest path, when the states hold more than a cell (e.g. a position and a direction)
+    pub fn cells_on_paths<P: Hash + Eq>(&self, cell: impl Fn(&S) -> P) -> FxHashSet<P> {
+        return self.states_on_paths().iter().map(cell).collect();
+    }
+}
+
+/// Like `dijkstra`, but keeps every predecessor reaching a state at its best cost, to find all the shortest paths.
+/// Only a strictly better cost queues a state again, an equal cost just adds a predecessor.
+/// The moves must cost more than 0 (panics otherwise), a move costing 0 could make a cycle of predecessors
+pub fn dijkstra_all<S, C, I>(start: S, mut neighbours: impl FnMut(&S) -> I, mut is_goal: impl FnMut(&S) -> bool) -> Option<ShortestPaths<S, C>>
+where
+    S: Copy + Hash + Eq,
+    C: Ord + Copy + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut queue = PriorityQueue::new();
+    let mut costs: FxHashMap<S, C> = FxHashMap::default();
+    let mut predecessors: FxHashMap<S, Vec<S>> = FxHashMap::default();
+    let mut goals = vec![];
+    let mut best: Option<C> = None;
+    costs.insert(start, C::default());
+    queue.push(Node {
+        state: start,
+        distance: C::default(),
+        priority: C::default(),
+    });
+    while let Some(node) = queue.pop() {
+        if best.is_some_and(|b| node.distance > b) {
+            break;
+        }
+        if is_goal(&node.state) {
+            best = Some(node.distance);
+            goals.push(node.state);
+            continue;
+        }
+        for (next, step) in neighbours(&node.state) {
+            assert!(step > C::default(), "dijkstra_all needs moves costing more than 0");
+            let cost = node.distance + step;
+            match costs.get(&next) {
+                Some(c) if *c < cost => continue,
+                Some(c) if *c == cost => {
+                    predecessors.entry(next).or_default().push(node.state);
+                    continue;
+                }
+                _ => {}
+            }
+            costs.insert(next, cost);
+            predecessors.insert(next, vec![node.state]);
+            queue.push(Node {
+                state: next,
+                distance: cost,
+                priority: cost,
+            });
+        }
+    }
+    return best.map(|cost| ShortestPaths {
+        start,
+        cost,
+        goals,
+        costs,
+        predecessors,
+    });
+}
+
+/// Neighbour function of a map for `bfs`: the adjacent cells accepted by `passable`
+pub fn map_neighbours<'a, T>(map: &'a Map2D<T>, passable: impl Fn(&T) -> bool + 'a) -> impl FnMut(&Pos) -> Vec<Pos> + 'a {
+    return move |pos| map.neighbours(pos).filter(|next| passable(map.get(next))).collect();
+}
+
+/// Neighbour function of a map for `dijkstra` and `astar`: the adjacent cells with the cost to enter them, `None` for a wall
+pub fn map_weighted_neighbours<'a, T, C>(map: &'a Map2D<T>, cost: impl Fn(&T) -> Option<C> + 'a) -> impl FnMut(&Pos) -> Vec<(Pos, C)> + 'a {
+    return move |pos| map.neighbours(pos).filter_map(|next| cost(map.get(&next)).map(|c| (next, c))).collect();
+}
+
+/// Distance on a grid moving in the 4 directions, a heuristic for `astar`
+pub fn manhattan(from: &Pos, to: &Pos) -> usize {
+    return from.x.abs_diff(to.x) + from.y.abs_diff(to.y);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map2d::Direction;
+
+    fn open_grid() -> (Map2D<char>, Pos, Pos) {
+        let lines = ["S..", "...", "..E"];
+        let (map, markers) = Map2D::from_lines_with_positions(&lines, &['S', 'E'], |c| c).unwrap();
+        return (map, markers[&'S'][0], markers[&'E'][0]);
+    }
+
+    #[test]
+    fn all_shortest_paths_of_an_open_grid() {
+        let (map, start, end) = open_grid();
+        let paths = dijkstra_all(start, map_weighted_neighbours(&map, |_| Some(1)), |pos| *pos == end).unwrap();
+        assert_eq!(paths.cost, 4);
+        assert_eq!(paths.goals, vec![end]);
+        assert_eq!(paths.count_paths(), 6);
+        assert_eq!(paths.states_on_paths().len(), 9);
+        assert_eq!(paths.predecessors(&end).len(), 2);
+        assert!(paths.predecessors(&start).is_empty());
+    }
+
+    #[test]
+    fn paths_joining_after_different_routes() {
+        let edges = |n: &u8| match n {
+            0 => vec![(1, 1), (2, 2)],
+            1 => vec![(3, 2)],
+            2 => vec![(3, 1), (4, 5)],
+            3 => vec![(4, 1)],
+            _ => vec![],
+        };
+        let paths = dijkstra_all(0u8, edges, |n| *n == 4).unwrap();
+        assert_eq!(paths.cost, 4);
+        assert_eq!(paths.count_paths(), 2);
+        assert_eq!(paths.states_on_paths(), FxHashSet::from_iter([0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn cells_on_paths_with_directions() {
+        // Turning costs 1000: both ways around the wall are best paths
+        let lines = ["#####", "#...#", "#S#E#", "#...#", "#####"];
+        let (map, markers) = Map2D::from_lines_with_positions(&lines, &['S', 'E'], |c| c).unwrap();
+        let (start, end) = (markers[&'S'][0], markers[&'E'][0]);
+        let moves = |(pos, dir): &(Pos, Direction)| {
+            let mut moves = vec![((*pos, *dir.turn_clockwise()), 1000), ((*pos, *dir.turn_counterclockwise()), 1000)];
+            if let Some(next) = map.move_pos(pos, dir).filter(|next| *map.get(next) != '#') {
+                moves.push(((next, *dir), 1));
+            }
+            moves
+        };
+        let paths = dijkstra_all((start, Direction::DOWN), moves, |(pos, _)| *pos == end).unwrap();
+        assert_eq!(paths.cost, 2004);
+        assert_eq!(paths.count_paths(), 1);
+        let paths = dijkstra_all((start, Direction::RIGHT), moves, |(pos, _)| *pos == end).unwrap();
+        assert_eq!(paths.cost, 3004);
+        assert_eq!(paths.count_paths(), 2);
+        let cells = paths.cells_on_paths(|(pos, _)| *pos);
+        assert_eq!(cells.len(), 8);
+        assert!(cells.contains(&Pos { x: 2, y: 1 }) && cells.contains(&Pos { x: 2, y: 3 }));
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let (map, start, end) = open_grid();
+        assert!(dijkstra_all(start, map_weighted_neighbours(&map, |c| (*c != 'E').then_some(1)), |pos| *pos == end).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "more than 0")]
+    fn moves_costing_0_are_rejected() {
+        let edges = |n: &u8| match n {
+            0 => vec![(1, 1), (2, 1)],
+            2 => vec![(1, 0)],
+            1 => vec![(3, 1)],
+            _ => vec![],
+        };
+        dijkstra_all(0u8, edges, |n| *n == 3);
+    }
+
+    #[test]
+    fn single_searches_agree() {
+        let lines = ["S..#....", ".#.#.##.", ".#...#..", ".####.#.", "......#E"];
+        let (map, markers) = Map2D::from_lines_with_positions(&lines, &['S', 'E'], |c| c).unwrap();
+        let (start, end) = (markers[&'S'][0], markers[&'E'][0]);
+        let found = bfs(start, map_neighbours(&map, |c| *c != '#'), |pos| *pos == end).unwrap();
+        assert_eq!(found.cost, 15);
+        assert_eq!((found.path[0], found.path[15]), (start, end));
+        let weighted = map_weighted_neighbours(&map, |c| (*c != '#').then_some(1));
+        assert_eq!(dijkstra(start, weighted, |pos| *pos == end).unwrap().cost, 15);
+        let weighted = map_weighted_neighbours(&map, |c| (*c != '#').then_some(1));
+        assert_eq!(astar(start, weighted, |pos| manhattan(pos, &end), |pos| *pos == end).unwrap().cost, 15);
+    }
+}
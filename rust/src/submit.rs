@@ -0,0 +1,307 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::answers::AnswerStore;
+use crate::fetch::{FetchError, Fetcher};
+use crate::utils::{self, Answer, Dataset, Part};
+
+/// Response of the server to a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    /// Submitted too recently after a previous answer, to be submitted again later
+    Wait,
+    /// The part is already solved (or not unlocked yet)
+    WrongLevel,
+    Unknown,
+}
+
+impl Verdict {
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too_high",
+            Verdict::TooLow => "too_low",
+            Verdict::Wrong => "wrong",
+            Verdict::Wait => "wait",
+            Verdict::WrongLevel => "wrong_level",
+            Verdict::Unknown => "unknown",
+        }
+    }
+
+    fn from_name(name: &str) -> Verdict {
+        match name {
+            "correct" => Verdict::Correct,
+            "too_high" => Verdict::TooHigh,
+            "too_low" => Verdict::TooLow,
+            "wrong" => Verdict::Wrong,
+            "wait" => Verdict::Wait,
+            "wrong_level" => Verdict::WrongLevel,
+            _ => Verdict::Unknown,
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+
+    /// Reads the verdict from the page answered by the server
+    fn from_page(page: &str) -> Verdict {
+        if page.contains("That's the right answer") {
+            Verdict::Correct
+        } else if page.contains("answer is too high") {
+            Verdict::TooHigh
+        } else if page.contains("answer is too low") {
+            Verdict::TooLow
+        } else if page.contains("That's not the right answer") {
+            Verdict::Wrong
+        } else if page.contains("You gave an answer too recently") {
+            Verdict::Wait
+        } else if page.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else {
+            Verdict::Unknown
+        }
+    }
+}
+
+/// Submitted answers with their verdict, stored in `submissions.toml` as:
+/// ```toml
+/// [day_1.part1]
+/// 1882713 = "too_low"
+/// 1882714 = "correct"
+/// ```
+pub struct SubmissionHistory {
+    path: PathBuf,
+    content: Table,
+}
+
+impl SubmissionHistory {
+    pub fn load(path: PathBuf) -> SubmissionHistory {
+        let content = match fs::read_to_string(&path) {
+            Ok(text) => text
+                .parse::<Table>()
+                .unwrap_or_else(|e| panic!("Invalid submissions file {}: {}", path.display(), e)),
+            Err(_) => Table::new(),
+        };
+        SubmissionHistory { path, content }
+    }
+
+    fn part_table(&self, day: u8, part: Part) -> Option<&Table> {
+        self.content
+            .get(&format!("day_{}", day))?
+            .get(format!("part{}", part.number()))?
+            .as_table()
+    }
+
+    /// Submitted answers of the part with their verdict
+    pub fn verdicts(&self, day: u8, part: Part) -> Vec<(Answer, Verdict)> {
+        self.part_table(day, part)
+            .map(|table| {
+                table
+                    .iter()
+                    .map(|(answer, verdict)| (Answer::from(answer.as_str()), Verdict::from_name(verdict.as_str().unwrap_or_default())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Bounds (exclusive) of a numeric answer, from the answers known to be too low and too high
+    pub fn bounds(&self, day: u8, part: Part) -> (Option<i128>, Option<i128>) {
+        let verdicts = self.verdicts(day, part);
+        let numbers = |expected: Verdict| {
+            verdicts
+                .iter()
+                .filter(move |(_, verdict)| *verdict == expected)
+                .filter_map(|(answer, _)| answer.as_str().parse::<i128>().ok())
+        };
+        (numbers(Verdict::TooLow).max(), numbers(Verdict::TooHigh).min())
+    }
+
+    /// Reason not to submit the answer, if already known right, wrong or out of bounds
+    pub fn refusal(&self, day: u8, part: Part, answer: &Answer) -> Option<String> {
+        let verdicts = self.verdicts(day, part);
+        if let Some((correct, _)) = verdicts.iter().find(|(_, verdict)| *verdict == Verdict::Correct) {
+            return Some(format!("already solved with {}", correct));
+        }
+        if let Some((_, verdict)) = verdicts.iter().find(|(known, verdict)| known == answer && verdict.is_wrong()) {
+            return Some(format!("{} already submitted ({})", answer, verdict.name()));
+        }
+        let value = answer.as_str().parse::<i128>().ok()?;
+        match self.bounds(day, part) {
+            (Some(low), _) if value <= low => Some(format!("{} is too low, {} was already too low", answer, low)),
+            (_, Some(high)) if value >= high => Some(format!("{} is too high, {} was already too high", answer, high)),
+            _ => None,
+        }
+    }
+
+    pub fn record(&mut self, day: u8, part: Part, answer: &Answer, verdict: Verdict) {
+        let day_table = self
+            .content
+            .entry(format!("day_{}", day))
+            .or_insert_with(|| Value::Table(Table::new()));
+        let part_table = day_table
+            .as_table_mut()
+            .expect("day entry should be a table")
+            .entry(format!("part{}", part.number()))
+            .or_insert_with(|| Value::Table(Table::new()));
+        part_table
+            .as_table_mut()
+            .expect("part entry should be a table")
+            .insert(answer.as_str().to_string(), Value::String(verdict.name().to_string()));
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = fs::write(&self.path, self.content.to_string()) {
+            println!("Cannot write submissions file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+pub fn history_file() -> PathBuf {
+    utils::data_dir().join("submissions.toml")
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    /// Not sent, the history already tells the answer is wrong (or the part solved)
+    Refused(String),
+    Fetch(FetchError),
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::Refused(reason) => write!(f, "Not submitted, {}", reason),
+            SubmitError::Fetch(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Sends the answer unless the history refuses it, then records the verdict.
+/// A correct answer becomes the expected answer of the real dataset in the answers file
+pub fn submit(fetcher: &Fetcher, history_path: &Path, answers_path: &Path, day: u8, part: Part, answer: &Answer) -> Result<Verdict, SubmitError> {
+    let mut history = SubmissionHistory::load(history_path.to_path_buf());
+    if let Some(reason) = history.refusal(day, part, answer) {
+        return Err(SubmitError::Refused(reason));
+    }
+
+    let form = [("level", part.number().to_string()), ("answer", answer.as_str().to_string())];
+    let page = fetcher
+        .day_path(day, "/answer")
+        .and_then(|path| fetcher.post(&path, &form))
+        .map_err(SubmitError::Fetch)?;
+    let verdict = Verdict::from_page(&page);
+    if verdict == Verdict::Correct || verdict.is_wrong() {
+        history.record(day, part, answer, verdict);
+        history.save();
+    }
+    if verdict == Verdict::Correct {
+        let mut answers = AnswerStore::load(answers_path.to_path_buf());
+        answers.set(day, part, &Dataset::Real, answer);
+        answers.save();
+    }
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::fetch::stand_in::serve;
+
+    fn respond(path: &str) -> (u16, String) {
+        match path {
+            "/2023/day/3/answer" => (200, "<article><p>That's the right answer! You are one gold star closer.</p></article>".to_string()),
+            "/2023/day/4/answer" => (200, "<article><p>That's not the right answer; your answer is too high.</p></article>".to_string()),
+            _ => (404, "Not found".to_string()),
+        }
+    }
+
+    /// Temporary history and answers files
+    fn temp_files(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("aoc_submit_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        (dir.join("submissions.toml"), dir.join("answers.toml"))
+    }
+
+    fn fetcher(url: &str) -> Fetcher {
+        Fetcher::new(url, Some("secret".to_string()))
+            .with_year(2023)
+            .with_min_delay(Duration::ZERO)
+    }
+
+    #[test]
+    fn verdict_from_page() {
+        assert_eq!(Verdict::from_page("That's the right answer!"), Verdict::Correct);
+        assert_eq!(Verdict::from_page("That's not the right answer; your answer is too high."), Verdict::TooHigh);
+        assert_eq!(Verdict::from_page("That's not the right answer; your answer is too low."), Verdict::TooLow);
+        assert_eq!(Verdict::from_page("That's not the right answer."), Verdict::Wrong);
+        assert_eq!(Verdict::from_page("You gave an answer too recently; you have to wait"), Verdict::Wait);
+        assert_eq!(Verdict::from_page("You don't seem to be solving the right level."), Verdict::WrongLevel);
+        assert_eq!(Verdict::from_page("<html></html>"), Verdict::Unknown);
+    }
+
+    #[test]
+    fn known_wrong_or_out_of_bounds_answers_are_refused() {
+        let (history_path, answers_path) = temp_files("refused");
+        let mut history = SubmissionHistory::load(history_path.clone());
+        history.record(3, Part::Part1, &Answer::from(100), Verdict::TooLow);
+        history.record(3, Part::Part1, &Answer::from(200), Verdict::TooHigh);
+        history.record(3, Part::Part1, &Answer::from(150), Verdict::Wrong);
+        history.save();
+
+        let history = SubmissionHistory::load(history_path.clone());
+        assert_eq!(history.bounds(3, Part::Part1), (Some(100), Some(200)));
+        assert_eq!(history.bounds(3, Part::Part2), (None, None));
+        assert!(history.refusal(3, Part::Part1, &Answer::from(150)).is_some());
+        assert!(history.refusal(3, Part::Part1, &Answer::from(100)).is_some());
+        assert!(history.refusal(3, Part::Part1, &Answer::from(250)).is_some());
+        assert!(history.refusal(3, Part::Part1, &Answer::from(170)).is_none());
+        assert!(history.refusal(3, Part::Part1, &Answer::from("abc")).is_none());
+
+        let (url, requests) = serve(respond);
+        let result = submit(&fetcher(&url), &history_path, &answers_path, 3, Part::Part1, &Answer::from(80));
+        assert!(matches!(result, Err(SubmitError::Refused(_))));
+        assert!(requests.lock().unwrap().is_empty());
+        fs::remove_dir_all(history_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn correct_answer_is_recorded_as_expected_answer() {
+        let (history_path, answers_path) = temp_files("correct");
+        let (url, requests) = serve(respond);
+        let verdict = submit(&fetcher(&url), &history_path, &answers_path, 3, Part::Part2, &Answer::from(42));
+        assert_eq!(verdict.unwrap(), Verdict::Correct);
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /2023/day/3/answer"));
+        assert!(request.contains("session=secret"));
+        assert!(request.ends_with("level=2&answer=42"));
+
+        let history = SubmissionHistory::load(history_path.clone());
+        assert_eq!(history.verdicts(3, Part::Part2), vec![(Answer::from(42), Verdict::Correct)]);
+        assert!(history.refusal(3, Part::Part2, &Answer::from(43)).is_some());
+        let answers = AnswerStore::load(answers_path);
+        assert_eq!(answers.get(3, Part::Part2, &Dataset::Real), Some(Answer::from(42)));
+        fs::remove_dir_all(history_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn wrong_answer_is_only_recorded_in_history() {
+        let (history_path, answers_path) = temp_files("wrong");
+        let (url, _) = serve(respond);
+        let verdict = submit(&fetcher(&url), &history_path, &answers_path, 4, Part::Part1, &Answer::from(500));
+        assert_eq!(verdict.unwrap(), Verdict::TooHigh);
+        assert_eq!(SubmissionHistory::load(history_path.clone()).bounds(4, Part::Part1), (None, Some(500)));
+        assert!(!answers_path.exists());
+        fs::remove_dir_all(history_path.parent().unwrap()).unwrap();
+    }
+}
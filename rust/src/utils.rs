@@ -1,108 +1,178 @@
 use std::fmt::Display;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Error, Lines};
-use std::path::Path;
-use std::sync::atomic::AtomicUsize;
-use std::time::Instant;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::answers;
+use crate::bench::{self, BenchConfig, BenchStats};
+use crate::logger;
+use crate::report::{self, RunResult, Status};
+
+pub const DATA_DIR: &str = "../data";
 
 #[macro_export]
 macro_rules!
 log {
-    ($level:tt,$ctxt:expr,$msg:expr) => (
-        $ctxt.$level(|| println!($msg))
-    );
-    ($level:tt, $ctxt:expr,$msg:expr, $($other:expr) ,*) => (
-        $ctxt.$level(|| println!($msg,$($other , )+))
+    ($level:tt, $ctxt:expr, $($arg:tt)+) => (
+        $ctxt.$level(format_args!($($arg)+))
     );
 }
 
-#[macro_export]
-macro_rules! check_result {
-    ($ctxt:expr, [$res_p1:expr, $res_p2:expr ], [ $res_p1_test:expr,$res_p1_real:expr, $res_p2_test:expr, $res_p2_real:expr ] ) => {
-        if $ctxt.is_bench() {
-            return;
-        }
-        if $ctxt.has_part() {
-            panic!("Shoudn't be call in separate run context")
-        }
-        if ($ctxt.is_test()) {
-            $ctxt.check_both(($res_p1, $res_p2), ($res_p1_test, $res_p2_test))
-        } else {
-            $ctxt.check_both(($res_p1, $res_p2), ($res_p1_real, $res_p2_real))
-        }
-    };
+/// Data directories tried in order: relative to the working directory, then to the crate (e.g. for `cargo test`)
+fn data_dirs() -> Vec<PathBuf> {
+    return vec![PathBuf::from(DATA_DIR), Path::new(env!("CARGO_MANIFEST_DIR")).join(DATA_DIR)];
+}
 
-    ($ctxt:expr, $res:expr, [ $res1:expr, $res2:expr ] ) => {
-        if $ctxt.is_bench() {
-            return;
-        }
+/// First existing data directory
+pub fn data_dir() -> PathBuf {
+    let dirs = data_dirs();
+    return dirs.iter().find(|dir| dir.is_dir()).cloned().unwrap_or_else(|| dirs[0].clone());
+}
 
-        if !$ctxt.has_part() {
-            panic!("Shoudn't be call in mono run context")
-        }
-        if ($ctxt.is_test()) {
-            $ctxt.check($res, $res1)
-        } else {
-            $ctxt.check($res, $res2)
-        }
-    };
+/// Files that may hold the dataset, in the order they are tried: the part specific file then the default one, in each data directory
+fn candidate_files(day: &u8, part: Option<Part>, is_test: &Dataset) -> Vec<PathBuf> {
+    let mut names = vec![];
+    if let Some(p) = part {
+        names.push(format!("day_{}_{}{}.dat", day, p.number(), is_test.file_suffix()));
+    }
+    names.push(format!("day_{}{}.dat", day, is_test.file_suffix()));
+    return data_dirs()
+        .iter()
+        .flat_map(|dir| names.iter().map(|name| dir.join(name)))
+        .collect();
 }
 
-fn read_lines_internal<P>(filename: P) -> Result<Lines<BufReader<File>>, Error>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+fn get_applicable_filename(day: &u8, part: Option<Part>, is_test: &Dataset) -> PathBuf {
+    let candidates = candidate_files(day, part, is_test);
+    return candidates
+        .iter()
+        .find(|path| path.exists())
+        .cloned()
+        .unwrap_or_else(|| data_file(day, is_test));
 }
 
-fn get_applicable_filename_default(day: &u8, is_test: &Dataset) -> String {
-    return format!(
-        "../data/day_{}{}.dat",
-        day,
-        match is_test {
-            Dataset::Test => "_test",
-            _ => "",
-        }
-    );
+/// File of the dataset (shared by both parts) in the data directory
+pub fn data_file(day: &u8, data_set: &Dataset) -> PathBuf {
+    return data_dir().join(data_file_name(day, data_set));
 }
 
-fn get_applicable_filename(day: &u8, part: Option<Part>, is_test: &Dataset) -> String {
-    return part
-        .map(|p| {
-            format!(
-                "../data/day_{}_{}{}.dat",
-                day,
-                match p {
-                    Part::Part1 => 1,
-                    Part::Part2 => 2,
-                },
-                match is_test {
-                    Dataset::Test => "_test",
-                    _ => "",
-                }
-            )
+/// Name of the file of a dataset (e.g. `day_5_test.dat`)
+pub fn data_file_name(day: &u8, data_set: &Dataset) -> String {
+    return format!("day_{}{}.dat", day, data_set.file_suffix());
+}
+
+fn list_test_names(prefix: &str) -> Vec<String> {
+    std::fs::read_dir(data_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().into_string().ok()?;
+                    let name = file_name.strip_prefix(prefix)?.strip_suffix(".dat")?;
+                    Some(name.to_string())
+                })
+                .collect()
         })
-        .filter(|name| std::path::Path::new(name.as_str()).exists())
-        .unwrap_or_else(|| get_applicable_filename_default(day, is_test));
+        .unwrap_or_default()
 }
 
-pub fn read_lines(day: &u8, part: Option<Part>, is_test: &Dataset) -> Option<Lines<BufReader<File>>> {
-    let f = read_lines_internal(get_applicable_filename(day, part, is_test));
+/// Lists the datasets of a day (and part when run per part): the default test, the named tests then the real one.
+/// The default test is skipped when only named tests exist
+pub fn list_datasets(day: &u8, part: Option<Part>) -> Vec<Dataset> {
+    let mut names = list_test_names(&format!("day_{}_test_", day));
+    if let Some(p) = part {
+        names.extend(list_test_names(&format!("day_{}_{}_test_", day, p.number())));
+    }
+    names.sort();
+    names.dedup();
+
+    let mut datasets = vec![];
+    if names.is_empty() || get_applicable_filename(day, part, &Dataset::Test).exists() {
+        datasets.push(Dataset::Test);
+    }
+    datasets.extend(names.into_iter().map(Dataset::NamedTest));
+    datasets.push(Dataset::Real);
+    datasets
+}
 
-    return match f {
-        Ok(lines) => Some(lines),
-        Err(_) => {
-            println!("No file found");
-            None
+#[derive(Debug)]
+pub enum InputError {
+    /// None of the attempted files exists
+    NotFound(Vec<PathBuf>),
+    Unreadable(String, io::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::NotFound(paths) => {
+                let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "No input file found, tried {}", paths.join(", "))
+            }
+            InputError::Unreadable(source, e) => write!(f, "Cannot read {}: {}", source, e),
         }
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<String>, InputError> {
+    let file = File::open(path).map_err(|e| InputError::Unreadable(path.display().to_string(), e))?;
+    return BufReader::new(file)
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| InputError::Unreadable(path.display().to_string(), e));
+}
+
+/// Reads the dataset from the data directory, failing with the attempted paths when no file exists
+pub fn read_lines(day: &u8, part: Option<Part>, is_test: &Dataset) -> Result<Vec<String>, InputError> {
+    let candidates = candidate_files(day, part, is_test);
+    return match candidates.iter().find(|path| path.exists()) {
+        Some(path) => read_file(path),
+        None => Err(InputError::NotFound(candidates)),
     };
 }
 
+static STDIN_LINES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Input read instead of the datasets of the data directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Path(PathBuf),
+    /// Read once, then shared by all the runs
+    Stdin,
+    /// Input given as a string, mostly for the tests
+    Inline(String),
+}
+
+impl InputSource {
+    /// `-` for stdin, a path otherwise
+    pub fn from_arg(value: &str) -> InputSource {
+        if value == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::Path(PathBuf::from(value))
+        }
+    }
+
+    pub fn lines(&self) -> Result<Vec<String>, InputError> {
+        match self {
+            InputSource::Path(path) if path.exists() => read_file(path),
+            InputSource::Path(path) => Err(InputError::NotFound(vec![path.clone()])),
+            InputSource::Stdin => Ok(STDIN_LINES.get_or_init(|| io::stdin().lock().lines().map_while(Result::ok).collect()).clone()),
+            InputSource::Inline(text) => Ok(text.lines().map(|l| l.to_string()).collect()),
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Copy)]
 pub enum Mode {
     STANDARD,
-    BENCH(u16),
+    BENCH(BenchConfig),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -111,17 +181,72 @@ pub enum Part {
     Part2,
 }
 
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub enum LogLevel {
     ERROR = 0,
     INFO = 1,
     DEBUG = 2,
+    TRACE = 3,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl Part {
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::Part1 => 1,
+            Part::Part2 => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Dataset {
     Test,
+    /// Additional example read from `day_N_test_<name>.dat` (or `day_N_<part>_test_<name>.dat`)
+    NamedTest(String),
     Real,
+    /// Input given by an `InputSource`, without expected answers
+    Custom,
+}
+
+impl Dataset {
+    pub fn name(&self) -> String {
+        match self {
+            Dataset::Test => "test".to_string(),
+            Dataset::NamedTest(name) => format!("test_{}", name),
+            Dataset::Real => "real".to_string(),
+            Dataset::Custom => "custom".to_string(),
+        }
+    }
+
+    pub fn is_test(&self) -> bool {
+        matches!(self, Dataset::Test | Dataset::NamedTest(_))
+    }
+
+    fn file_suffix(&self) -> String {
+        match self {
+            Dataset::Real => String::new(),
+            _ => format!("_{}", self.name()),
+        }
+    }
+
+    /// A restriction on the default test also accepts the named tests
+    fn accepts(&self, other: &Dataset) -> bool {
+        match self {
+            Dataset::Test => other.is_test(),
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for Dataset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dataset::Test => write!(f, "Test"),
+            Dataset::NamedTest(name) => write!(f, "Test_{}", name),
+            Dataset::Real => write!(f, "Real"),
+            Dataset::Custom => write!(f, "Custom"),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -131,14 +256,26 @@ pub enum Active {
 }
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Context {
     log_level: LogLevel,
     day: u8,
     data_set: Dataset,
     is_debug: bool,
     is_bench: bool,
+    is_record: bool,
     part: Option<Part>,
+    bench_tag: Option<String>,
+    /// When set (parallel runs), the logs are kept to be printed at once at the end of the run
+    output: Option<Arc<Mutex<String>>>,
+    /// Set once the run has timed out, to silence the abandoned thread
+    muted: Option<Arc<AtomicBool>>,
+    /// When set, the log lines of the run (with their part) are kept for the report
+    captured: Option<CapturedLogs>,
 }
+/// Log lines of a run with the part logging them (none for the lines shared by both parts)
+type CapturedLogs = Arc<Mutex<Vec<(Option<Part>, String)>>>;
+
 static NB_FAILURES: AtomicUsize = AtomicUsize::new(0);
 
 #[allow(dead_code)]
@@ -152,48 +289,96 @@ impl Context {
     }
 
     fn new(day: &u8, options: &RunOption, part: Option<Part>, data_set: &Dataset) -> Context {
-        let log_level = options.get_log_level();
-        let is_debug = options.debug.unwrap_or(false);
+        let log_level = options.get_log_level(day);
+        let is_debug = log_level >= LogLevel::DEBUG;
         let is_bench = options
             .mode
-            .map(|m| match m {
-                Mode::BENCH(_) => true,
-                _ => false,
-            })
+            .map(|m| matches!(m, Mode::BENCH(_)))
             .unwrap_or(false);
         return Context {
-            log_level: log_level,
+            log_level,
             day: *day,
-            data_set: *data_set,
-            part: part,
-            is_debug: is_debug,
-            is_bench: is_bench,
+            data_set: data_set.clone(),
+            part,
+            is_debug,
+            is_bench,
+            is_record: options.is_record(),
+            bench_tag: None,
+            output: None,
+            muted: None,
+            captured: None,
         };
     }
 
-    fn log(&self, log_level: LogLevel, print_fct: impl Fn()) {
-        if log_level <= self.log_level {
-            match &self.part {
-                Some(p) => {
-                    print!("[Day {}/{:?}/{:?}]", self.day, p, self.data_set)
-                }
-                None => print!("[Day {}/ALL/{:?}]", self.day, self.data_set),
-            }
-            print_fct();
+    fn with_bench_tag(&self, bench_tag: &str) -> Context {
+        Context {
+            bench_tag: Some(bench_tag.to_string()),
+            ..self.clone()
         }
     }
 
-    #[allow(dead_code)]
-    pub fn debug(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::DEBUG, print_fct);
+    fn with_part(&self, part: Part) -> Context {
+        Context {
+            part: Some(part),
+            ..self.clone()
+        }
     }
 
-    pub fn error(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::ERROR, print_fct);
+    fn is_muted(&self) -> bool {
+        self.muted.as_ref().is_some_and(|m| m.load(std::sync::atomic::Ordering::Relaxed))
     }
 
-    pub fn info(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::INFO, print_fct);
+    fn log(&self, log_level: LogLevel, message: std::fmt::Arguments) {
+        if self.is_muted() || log_level > self.log_level {
+            return;
+        }
+        let tag = self.bench_tag.as_ref().map(|t| format!("/{}", t)).unwrap_or_default();
+        let part = self.part.map(|p| format!("{:?}", p)).unwrap_or("ALL".to_string());
+        let prefix = format!("Day {}/{}/{}{}", self.day, part, self.data_set, tag);
+        let line = logger::format_line(log_level, &prefix, &message.to_string());
+        if let Some(captured) = &self.captured {
+            captured.lock().unwrap().push((self.part, line.trim_end().to_string()));
+        }
+        match &self.output {
+            Some(output) => output.lock().unwrap().push_str(&line),
+            None => logger::write(&line),
+        }
+    }
+
+    pub fn trace(&self, message: std::fmt::Arguments) {
+        self.log(LogLevel::TRACE, message);
+    }
+
+    pub fn debug(&self, message: std::fmt::Arguments) {
+        self.log(LogLevel::DEBUG, message);
+    }
+
+    /// Logs a multi-line block (e.g. a `Map2D::render`) in debug mode, one log line per line.
+    /// The block is only formatted when debug logs are enabled
+    pub fn debug_block(&self, title: std::fmt::Arguments, block: &dyn Display) {
+        if self.is_muted() || LogLevel::DEBUG > self.log_level {
+            return;
+        }
+        self.log(LogLevel::DEBUG, title);
+        for line in block.to_string().lines() {
+            self.log(LogLevel::DEBUG, format_args!("{}", line));
+        }
+    }
+
+    pub fn error(&self, message: std::fmt::Arguments) {
+        self.log(LogLevel::ERROR, message);
+    }
+
+    pub fn info(&self, message: std::fmt::Arguments) {
+        self.log(LogLevel::INFO, message);
+    }
+
+    /// Captured log lines of the given part (including the lines shared by both parts)
+    fn captured_logs(&self, part: Part) -> Vec<String> {
+        self.captured
+            .as_ref()
+            .map(|c| c.lock().unwrap().iter().filter(|(p, _)| p.is_none_or(|p| p == part)).map(|(_, l)| l.clone()).collect())
+            .unwrap_or_default()
     }
 
     #[allow(dead_code)]
@@ -209,23 +394,130 @@ impl Context {
         self.is_bench
     }
 
+    /// Variant of the implementation to use, when benching several variants
+    pub fn bench_tag(&self) -> Option<&str> {
+        self.bench_tag.as_deref()
+    }
+
+    pub fn is_bench_tag(&self, bench_tag: &str) -> bool {
+        self.bench_tag() == Some(bench_tag)
+    }
+
     pub fn has_part(&self) -> bool {
-        return match self.part {
-            Some(_) => true,
-            None => false,
-        };
+        return self.part.is_some();
     }
 
     pub fn is_test(&self) -> bool {
-        return self.data_set == Dataset::Test;
+        return self.data_set.is_test();
+    }
+
+    /// Identifies the run as `day_N/part/dataset` (suffixed by `#tag` for a bench variant)
+    fn run_key(&self) -> String {
+        format!(
+            "day_{}/{}/{}{}",
+            self.day,
+            self.part.map(|p| format!("part{}", p.number())).unwrap_or("all".to_string()),
+            self.data_set.name(),
+            self.bench_tag.as_ref().map(|t| format!("#{}", t)).unwrap_or_default()
+        )
     }
 
-    pub fn check<T: Eq + Display>(&self, val: T, expected: T) {
-        if val == expected {
-            log!(info, self, "Result OK {}", val);
+    /// Identifies the parse phase of the run as `day_N/part/dataset/parse` (`all` when shared by both parts)
+    fn parse_key(&self) -> String {
+        format!("{}/parse", self.run_key())
+    }
+
+    fn expected(&self, part: Part) -> Option<Answer> {
+        answers::expected_answer(self.day, part, &self.data_set)
+    }
+
+    fn part_label(&self, part: Part) -> String {
+        if self.has_part() {
+            String::new()
         } else {
-            Context::incr_error();
-            log!(error, self, "Result KO >>>{}<<<< instead of {})", val, expected);
+            format!("{:?} ", part)
+        }
+    }
+
+    fn check_part(&self, part: Part, val: &Answer, expected: Option<Answer>) -> CheckOutcome {
+        let label = self.part_label(part);
+        let status = match &expected {
+            Some(expected) if val == expected => {
+                log!(info, self, "{}Result OK {}", label, val);
+                Status::Ok
+            }
+            Some(expected) => {
+                self.count_error();
+                log!(error, self, "{}Result KO >>>{}<<<< instead of {})", label, val, expected);
+                Status::Ko
+            }
+            None if self.is_record && self.data_set != Dataset::Custom && !self.is_muted() => {
+                answers::record_answer(self.day, part, &self.data_set, val);
+                log!(info, self, "{}Result {} recorded", label, val);
+                Status::Recorded
+            }
+            None => {
+                log!(info, self, "{}Result {} (no expected answer)", label, val);
+                Status::Unchecked
+            }
+        };
+        CheckOutcome {
+            part,
+            answer: val.clone(),
+            expected,
+            status,
+        }
+    }
+
+    pub fn check(&self, val: Answer) -> CheckOutcome {
+        let part = self.part.expect("Shoudn't be call in mono run context");
+        self.check_part(part, &val, self.expected(part))
+    }
+
+    /// Records a run that didn't end (panic or timeout) as a failure of its parts (for its bench variant) not solved yet
+    fn fail(&self, parts: &[Part], status: Status, message: &str) {
+        self.count_error();
+        log!(error, self, "Run {:?}: {}", status, message);
+        for part in parts {
+            if report::contains(self.day, part.number(), &self.data_set.name(), self.bench_tag.as_deref()) {
+                continue;
+            }
+            report::add(RunResult {
+                day: self.day,
+                part: part.number(),
+                dataset: self.data_set.name(),
+                bench_tag: self.bench_tag.clone(),
+                answer: String::new(),
+                expected: self.expected(*part).map(|e| e.as_str().to_string()),
+                status,
+                read_ms: 0.0,
+                parse_ms: 0.0,
+                solve_ms: 0.0,
+                message: Some(message.to_string()),
+                logs: self.captured_logs(*part),
+            });
+        }
+    }
+
+    fn report(&self, outcomes: Vec<CheckOutcome>, timings: SharedTimings, solve_ms: f64) {
+        if self.is_muted() {
+            return;
+        }
+        for outcome in outcomes {
+            report::add(RunResult {
+                day: self.day,
+                part: outcome.part.number(),
+                dataset: self.data_set.name(),
+                bench_tag: self.bench_tag.clone(),
+                answer: outcome.answer.as_str().to_string(),
+                expected: outcome.expected.map(|e| e.as_str().to_string()),
+                status: outcome.status,
+                read_ms: timings.read_ms as f64,
+                parse_ms: timings.parse_ms,
+                solve_ms,
+                message: None,
+                logs: self.captured_logs(outcome.part),
+            });
         }
     }
 
@@ -233,15 +525,31 @@ impl Context {
         NB_FAILURES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Counts a wrong answer, unless it comes from a run abandoned after a timeout (already counted)
+    fn count_error(&self) {
+        if !self.is_muted() {
+            Context::incr_error();
+        }
+    }
+
     pub fn get_errors() -> usize {
         NB_FAILURES.fetch_add(0, std::sync::atomic::Ordering::Relaxed)
     }
 
-    pub fn check_both<T: Eq + Display>(&self, val: (T, T), expected: (T, T)) {
+    pub fn check_both(&self, val: (Answer, Answer)) -> Vec<CheckOutcome> {
+        let expected = match (self.expected(Part::Part1), self.expected(Part::Part2)) {
+            (Some(expected_p1), Some(expected_p2)) => (expected_p1, expected_p2),
+            (expected_p1, expected_p2) => {
+                return vec![
+                    self.check_part(Part::Part1, &val.0, expected_p1),
+                    self.check_part(Part::Part2, &val.1, expected_p2),
+                ];
+            }
+        };
         if val.0 == expected.0 && val.1 == expected.1 {
             log!(info, self, "Result OK ({},{})", val.0, val.1);
         } else if val.0 != expected.0 && val.1 == expected.1 {
-            Context::incr_error();
+            self.count_error();
             log!(
                 error,
                 self,
@@ -252,7 +560,7 @@ impl Context {
                 expected.1
             );
         } else if val.0 != expected.0 {
-            Context::incr_error();
+            self.count_error();
             log!(
                 error,
                 self,
@@ -263,7 +571,7 @@ impl Context {
                 expected.1
             );
         } else if val.1 != expected.1 {
-            Context::incr_error();
+            self.count_error();
             log!(
                 error,
                 self,
@@ -274,80 +582,198 @@ impl Context {
                 expected.1
             );
         }
+        let status = |ok: bool| if ok { Status::Ok } else { Status::Ko };
+        vec![
+            CheckOutcome {
+                part: Part::Part1,
+                status: status(val.0 == expected.0),
+                answer: val.0,
+                expected: Some(expected.0),
+            },
+            CheckOutcome {
+                part: Part::Part2,
+                status: status(val.1 == expected.1),
+                answer: val.1,
+                expected: Some(expected.1),
+            },
+        ]
     }
 }
 
-pub fn run<F: Fn(&Context, &Vec<String>)>(context: Context, fct: &F, mode: &Mode) {
-    log!(info, &context, "Starting");
+pub struct CheckOutcome {
+    part: Part,
+    answer: Answer,
+    expected: Option<Answer>,
+    status: Status,
+}
 
-    let start_read = Instant::now();
-    let lines = to_lines(&context.day, context.part, &context.data_set);
-    let read_duration = start_read.elapsed().as_secs_f32() * 1000.0;
-    let nb_max = match *mode {
-        Mode::BENCH(nb) => nb,
-        _ => 1,
-    };
-    let start = Instant::now();
-    let mut count = 0;
-    while count < nb_max {
-        count += 1;
-        fct(&context, &lines);
+enum Timing {
+    Single(f32),
+    Bench(BenchStats),
+}
+
+impl Timing {
+    /// Duration of a single call (the median in bench mode)
+    fn ms(&self) -> f64 {
+        match self {
+            Timing::Single(duration) => *duration as f64,
+            Timing::Bench(stats) => stats.median,
+        }
+    }
+}
+
+fn run_iterations<R, F: Fn(&Context) -> R>(context: &Context, fct: &F, mode: &Mode) -> (R, Timing) {
+    match mode {
+        Mode::BENCH(config) => {
+            let (res, stats) = bench::bench(config, || fct(context));
+            (res, Timing::Bench(stats))
+        }
+        Mode::STANDARD => {
+            let start = Instant::now();
+            let res = fct(context);
+            (res, Timing::Single(start.elapsed().as_secs_f32() * 1000.0))
+        }
+    }
+}
+
+/// Durations (in ms) of the phases shared by the parts of a run
+#[derive(Clone, Copy)]
+struct SharedTimings {
+    read_ms: f32,
+    parse_ms: f64,
+}
+
+fn log_parse_timing(context: &Context, timing: &Timing, read_duration: f32) {
+    match timing {
+        Timing::Single(duration) => log!(info, context, "Parse {:.2} ms and {:.2} ms for read", duration, read_duration),
+        Timing::Bench(stats) => {
+            log!(info, context, "Parse bench {} and {:.2} ms for read", stats, read_duration);
+            bench::store_result(context.parse_key(), stats);
+        }
     }
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
+}
 
+fn log_timing(context: &Context, timing: &Timing) {
+    match timing {
+        Timing::Single(duration) => log!(info, context, "Duration {:.2} ms", duration),
+        Timing::Bench(stats) => {
+            log!(info, context, "Bench {}", stats);
+            bench::store_result(context.run_key(), stats);
+        }
+    }
+}
+
+fn log_variants(context: &Context, variants: &[(String, BenchStats)]) {
+    let fastest = variants.iter().map(|(_, stats)| stats.median).fold(f64::INFINITY, f64::min);
     log!(
         info,
         context,
-        "Duration {:.2} ms (avg {:.2} for #{} iterations) and {:.2} ms for read",
-        duration,
-        duration / nb_max as f32,
-        count,
-        read_duration
+        "Bench variants: {:<16} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8}",
+        "variant",
+        "median",
+        "mean",
+        "std dev",
+        "p95",
+        "min",
+        "max",
+        "ratio"
     );
+    for (tag, stats) in variants {
+        log!(
+            info,
+            context,
+            "Bench variants: {:<16} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>7.2}x",
+            tag,
+            stats.median,
+            stats.mean,
+            stats.std_dev,
+            stats.p95,
+            stats.min,
+            stats.max,
+            stats.median / fastest
+        );
+    }
 }
 
-#[allow(dead_code)]
-pub fn run_simult<F: Fn(&Context, &Vec<String>)>(context: Context, fct: &F, mode: &Mode) {
+/// Runs the solve phase on the parsed input, returning its statistics in bench mode
+fn run_solve<R, F: Fn(&Context) -> R>(
+    context: Context,
+    timings: SharedTimings,
+    fct: &F,
+    mode: &Mode,
+    check: impl Fn(&Context, R) -> Vec<CheckOutcome>,
+) -> Option<BenchStats> {
+    let (res, timing) = run_iterations(&context, fct, mode);
+    let outcomes = check(&context, res);
+    log_timing(&context, &timing);
+    context.report(outcomes, timings, timing.ms());
+    return match timing {
+        Timing::Bench(stats) => Some(stats),
+        Timing::Single(_) => None,
+    };
+}
+
+/// Reads and parses the dataset once for the given parts, recording their failure when the input cannot be read
+fn read_and_parse(context: &Context, parts: &[Part], solver: Solver, input: &Option<InputSource>, mode: &Mode) -> Option<(ParsedInput, SharedTimings)> {
     log!(info, context, "Starting");
 
+    let file_part = match solver {
+        Solver::PerPart { .. } => parts.first().copied(),
+        Solver::Simult { .. } => None,
+    };
     let start_read = Instant::now();
-    let lines = to_lines(&context.day, None, &context.data_set);
+    let lines = match input {
+        Some(source) => source.lines(),
+        None => read_lines(&context.day, file_part, &context.data_set),
+    };
+    let lines = match lines {
+        Ok(lines) => lines,
+        Err(e) => {
+            context.fail(parts, Status::Failed, &e.to_string());
+            return None;
+        }
+    };
     let read_duration = start_read.elapsed().as_secs_f32() * 1000.0;
 
-    let nb_max = match *mode {
-        Mode::BENCH(nb) => nb,
-        _ => 1,
+    let parse = match solver {
+        Solver::PerPart { parse, .. } => parse,
+        Solver::Simult { parse, .. } => parse,
     };
-    let start = Instant::now();
-    let mut count = 0;
-    while count < nb_max {
-        count += 1;
-        fct(&context, &lines);
-    }
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
-    log!(
-        info,
-        context,
-        "Duration {:.2} ms (avg {:.2} for #{} iterations) and {:.2} ms for read",
-        duration,
-        duration / nb_max as f32,
-        count,
-        read_duration
-    )
+    let (input, parse_timing) = run_iterations(context, &|c: &Context| parse(c, &lines), mode);
+    log_parse_timing(context, &parse_timing, read_duration);
+    let timings = SharedTimings {
+        read_ms: read_duration,
+        parse_ms: parse_timing.ms(),
+    };
+    return Some((input, timings));
 }
 
-pub fn to_lines(day: &u8, part: Option<Part>, data_set: &Dataset) -> Vec<String> {
-    return read_lines(day, part, data_set)
-        .map(|lines| lines.map(|l| l.unwrap()).collect())
-        .unwrap_or(vec![]);
+/// Solves the part of the context (both parts for a simultaneous solver) on the parsed input, for the bench variant of the context
+fn solve_parsed(context: Context, solver: Solver, input: &ParsedInput, timings: SharedTimings, mode: &Mode) -> Option<BenchStats> {
+    match solver {
+        Solver::PerPart { part1, part2, .. } => {
+            let puzzle = match context.part.expect("a part to solve") {
+                Part::Part1 => part1,
+                Part::Part2 => part2,
+            };
+            run_solve(context, timings, &|c: &Context| puzzle(c, input), mode, |c, res| vec![c.check(res)])
+        }
+        Solver::Simult { solve, .. } => run_solve(context, timings, &|c: &Context| solve(c, input), mode, |c, res| c.check_both(res)),
+    }
 }
 
 pub type DaysRestriction<'a> = &'a Option<Vec<u8>>;
+#[derive(Clone)]
 pub struct RunOption<'a> {
     active: Option<bool>,
     mode: Option<Mode>,
     debug: Option<bool>,
+    record: Option<bool>,
     part_restriction: Option<Part>,
+    dataset_restriction: Option<Dataset>,
+    bench_tags: Vec<String>,
+    timeout: Option<Duration>,
+    input: Option<InputSource>,
     days_restriction: DaysRestriction<'a>,
 }
 
@@ -358,9 +784,14 @@ impl<'a> RunOption<'a> {
     pub fn new(days_restriction: DaysRestriction<'a>) -> RunOption<'a> {
         RunOption {
             debug: None,
+            record: None,
             mode: None,
             active: None,
             part_restriction: None,
+            dataset_restriction: None,
+            bench_tags: vec![],
+            timeout: None,
+            input: None,
             days_restriction,
         }
     }
@@ -368,43 +799,79 @@ impl<'a> RunOption<'a> {
     #[allow(dead_code)]
     pub fn disabled() -> RunOption<'a> {
         RunOption {
-            debug: None,
-            mode: None,
             active: Some(false),
-            part_restriction: None,
-            days_restriction: &None,
+            ..RunOption::new(&None)
         }
     }
     #[allow(dead_code)]
     pub fn debug(&self) -> RunOption<'a> {
         RunOption {
             debug: Some(true),
-            mode: self.mode,
-            active: self.active,
-            part_restriction: self.part_restriction,
-            days_restriction: self.days_restriction,
+            ..self.clone()
         }
     }
 
     #[allow(dead_code)]
     pub fn bench(&self, nb: u16) -> RunOption<'a> {
+        self.bench_with(BenchConfig::new(nb))
+    }
+
+    #[allow(dead_code)]
+    pub fn bench_with(&self, config: BenchConfig) -> RunOption<'a> {
         RunOption {
-            active: self.active,
-            mode: Some(Mode::BENCH(nb)),
-            debug: self.debug,
-            part_restriction: self.part_restriction,
-            days_restriction: self.days_restriction,
+            mode: Some(Mode::BENCH(config)),
+            ..self.clone()
         }
     }
 
     #[allow(dead_code)]
     pub fn only(&self, part: Part) -> RunOption<'a> {
         RunOption {
-            active: self.active,
-            mode: self.mode,
-            debug: self.debug,
             part_restriction: Some(part),
-            days_restriction: self.days_restriction,
+            ..self.clone()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn only_dataset(&self, data_set: Dataset) -> RunOption<'a> {
+        RunOption {
+            dataset_restriction: Some(data_set),
+            ..self.clone()
+        }
+    }
+
+    /// Benches each of the given variants (see `Context::bench_tag`) and compares them side by side
+    #[allow(dead_code)]
+    pub fn with_bench_tags(&self, bench_tags: &[&str]) -> RunOption<'a> {
+        RunOption {
+            bench_tags: bench_tags.iter().map(|t| t.to_string()).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Fails the runs not finished after the given duration
+    #[allow(dead_code)]
+    pub fn with_timeout(&self, timeout: Duration) -> RunOption<'a> {
+        RunOption {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn record(&self) -> RunOption<'a> {
+        RunOption {
+            record: Some(true),
+            ..self.clone()
+        }
+    }
+
+    /// Reads the input from the given source instead of the datasets of the data directory
+    #[allow(dead_code)]
+    pub fn with_input(&self, input: InputSource) -> RunOption<'a> {
+        RunOption {
+            input: Some(input),
+            ..self.clone()
         }
     }
 
@@ -425,11 +892,13 @@ impl<'a> RunOption<'a> {
         self.mode.as_ref().unwrap_or(&Mode::STANDARD)
     }
 
-    fn get_log_level(&self) -> LogLevel {
-        if self.is_debug() {
-            LogLevel::DEBUG
+    /// Level of the day: its override, else DEBUG in debug mode, else the default level
+    fn get_log_level(&self, day: &u8) -> LogLevel {
+        let level = logger::day_level(*day);
+        if self.is_debug() && logger::day_override(*day).is_none() {
+            level.max(LogLevel::DEBUG)
         } else {
-            LogLevel::INFO
+            level
         }
     }
 
@@ -437,56 +906,425 @@ impl<'a> RunOption<'a> {
         self.debug.unwrap_or(false)
     }
 
+    fn is_record(&self) -> bool {
+        self.record.unwrap_or(false)
+    }
+
     fn is_part_enabled(&self, part: Part) -> bool {
         self.part_restriction.is_none() || self.part_restriction.unwrap() == part
     }
+
+    fn is_dataset_enabled(&self, data_set: &Dataset) -> bool {
+        self.dataset_restriction.as_ref().map(|r| r.accepts(data_set)).unwrap_or(true)
+    }
 }
 
-pub fn run_all<F: Fn(&Context, &Vec<String>)>(day: &u8, fct: &F, options: RunOption) {
+/// Lists the enabled (part, dataset) runs of a day, without part when both parts are computed at once.
+/// A single custom dataset is run when the input source is given
+fn selected_runs(day: &u8, per_part: bool, options: &RunOption) -> Vec<(Option<Part>, Dataset)> {
     if !options.is_active(day) {
-        return;
+        return vec![];
     }
+    let parts = if per_part {
+        [Part::Part1, Part::Part2]
+            .into_iter()
+            .filter(|p| options.is_part_enabled(*p))
+            .map(Some)
+            .collect()
+    } else {
+        vec![None]
+    };
+    parts
+        .into_iter()
+        .flat_map(|part| {
+            let datasets = if options.input.is_some() { vec![Dataset::Custom] } else { list_datasets(day, part) };
+            datasets.into_iter().map(move |data_set| (part, data_set))
+        })
+        .filter(|(_, data_set)| *data_set == Dataset::Custom || options.is_dataset_enabled(data_set))
+        .collect()
+}
 
-    let mode = options.get_mode();
+/// Answer of a puzzle part, kept as its textual representation so that numbers and strings can be compared alike
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer(String);
 
-    println!("");
-    println!("");
-    println!("[Day {}] run per part", day);
-    let start = Instant::now();
-    if options.is_part_enabled(Part::Part1) {
-        run(Context::new_part(day, &options, Part::Part1, &Dataset::Test), &fct, mode);
-        println!("");
-        run(Context::new_part(day, &options, Part::Part1, &Dataset::Real), &fct, mode);
-        println!("");
+macro_rules! impl_answer_from {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                Answer(value.to_string())
+            }
+        })*
+    };
+}
+
+impl_answer_from!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char, String, &str);
+
+impl Answer {
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
-    if options.is_part_enabled(Part::Part2) {
-        run(Context::new_part(day, &options, Part::Part2, &Dataset::Test), &fct, mode);
-        println!("");
-        run(Context::new_part(day, &options, Part::Part2, &Dataset::Real), &fct, mode);
-        println!("");
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
+}
+
+/// Day solved part by part: the lines are parsed once into `Input`, then each part is solved on it
+pub trait Solution {
+    type Input: Send + Sync + 'static;
+
+    fn parse(context: &Context, lines: &[String]) -> Self::Input;
+
+    fn part1(context: &Context, input: &Self::Input) -> Answer;
+
+    fn part2(context: &Context, input: &Self::Input) -> Answer;
+}
+
+/// Day computing both parts in a single call on the parsed input
+pub trait SimultSolution {
+    type Input: Send + Sync + 'static;
+
+    fn parse(context: &Context, lines: &[String]) -> Self::Input;
+
+    fn solve(context: &Context, input: &Self::Input) -> (Answer, Answer);
+}
+
+/// Parsed input of a day, with its type erased so that all the days fit in the registry.
+/// Shared by the threads solving each part
+type ParsedInput = Box<dyn Any + Send + Sync>;
+type ParseFn = fn(&Context, &[String]) -> ParsedInput;
+type PartFn = fn(&Context, &ParsedInput) -> Answer;
+type SimultFn = fn(&Context, &ParsedInput) -> (Answer, Answer);
+
+fn parse_input<S: Solution>(context: &Context, lines: &[String]) -> ParsedInput {
+    Box::new(S::parse(context, lines))
+}
+
+fn solve_part1<S: Solution>(context: &Context, input: &ParsedInput) -> Answer {
+    S::part1(context, input.downcast_ref().expect("input parsed by the same solution"))
+}
 
-    println!("[Day {}] done in {:.2} ms", day, duration);
+fn solve_part2<S: Solution>(context: &Context, input: &ParsedInput) -> Answer {
+    S::part2(context, input.downcast_ref().expect("input parsed by the same solution"))
+}
+
+fn parse_simult_input<S: SimultSolution>(context: &Context, lines: &[String]) -> ParsedInput {
+    Box::new(S::parse(context, lines))
+}
+
+fn solve_simult<S: SimultSolution>(context: &Context, input: &ParsedInput) -> (Answer, Answer) {
+    S::solve(context, input.downcast_ref().expect("input parsed by the same solution"))
+}
+
+#[derive(Clone, Copy)]
+pub enum Solver {
+    PerPart { parse: ParseFn, part1: PartFn, part2: PartFn },
+    Simult { parse: ParseFn, solve: SimultFn },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    PerPart,
+    Simult,
+}
+
+pub struct Day {
+    pub day: u8,
+    pub solver: Solver,
 }
 
 #[allow(dead_code)]
-pub fn run_all_simult<F: Fn(&Context, &Vec<String>)>(day: &u8, fct: &F, options: RunOption) {
-    if !options.is_active(day) {
-        return;
+impl Day {
+    pub const fn per_part<S: Solution>(day: u8) -> Day {
+        Day {
+            day,
+            solver: Solver::PerPart {
+                parse: parse_input::<S>,
+                part1: solve_part1::<S>,
+                part2: solve_part2::<S>,
+            },
+        }
+    }
+
+    pub const fn simult<S: SimultSolution>(day: u8) -> Day {
+        Day {
+            day,
+            solver: Solver::Simult {
+                parse: parse_simult_input::<S>,
+                solve: solve_simult::<S>,
+            },
+        }
+    }
+
+    pub fn kind(&self) -> RunKind {
+        match self.solver {
+            Solver::PerPart { .. } => RunKind::PerPart,
+            Solver::Simult { .. } => RunKind::Simult,
+        }
+    }
+
+    /// Part of the data files to read for the part: the part specific file (`day_N_2.dat`) can only be used when the parts are solved separately
+    pub fn file_part(&self, part: Part) -> Option<Part> {
+        if self.kind() == RunKind::PerPart { Some(part) } else { None }
     }
-    let mode = options.get_mode();
-    println!("");
-    println!("");
-    println!("[Day {}] run global", day);
-    let start = Instant::now();
-    run_simult(Context::new_all(day, &options, &Dataset::Test), fct, mode);
-    println!("");
-    run_simult(Context::new_all(day, &options, &Dataset::Real), fct, mode);
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
-    println!("");
 
-    println!("[Day {}] done in {:.2} ms", day, duration);
+    /// Solves a part on the given input, without checking nor logging the answer (e.g. for the tests)
+    pub fn solve(&self, part: Part, input: &InputSource) -> Result<Answer, InputError> {
+        Ok(self.solve_lines(part, &input.lines()?))
+    }
+
+    /// Solves the part on the given lines, without logs
+    pub fn solve_lines(&self, part: Part, lines: &[String]) -> Answer {
+        let context = Context::new(&self.day, &RunOption::new(&None), Some(part), &Dataset::Custom);
+        return match self.solver {
+            Solver::PerPart { parse, part1, part2 } => {
+                let parsed = parse(&context, lines);
+                match part {
+                    Part::Part1 => part1(&context, &parsed),
+                    Part::Part2 => part2(&context, &parsed),
+                }
+            }
+            Solver::Simult { parse, solve } => {
+                let (answer1, answer2) = solve(&context, &parse(&context, lines));
+                match part {
+                    Part::Part1 => answer1,
+                    Part::Part2 => answer2,
+                }
+            }
+        };
+    }
+
+    /// Runs each dataset in its own thread, so that a panic or a timeout only fails that run
+    pub fn run(&self, options: RunOption) {
+        if !options.is_active(&self.day) {
+            return;
+        }
+
+        let prefix = format!("Day {}", self.day);
+        logger::separator(self.day);
+        match self.kind() {
+            RunKind::PerPart => logger::log(self.day, LogLevel::INFO, &prefix, "run per part"),
+            RunKind::Simult => logger::log(self.day, LogLevel::INFO, &prefix, "run global"),
+        }
+        let start = Instant::now();
+        for job in self.jobs(&options) {
+            job.execute(false);
+            logger::separator(self.day);
+        }
+        let duration = start.elapsed().as_secs_f32() * 1000.0;
+
+        logger::log(self.day, LogLevel::INFO, &prefix, &format!("done in {:.2} ms", duration));
+    }
+
+    /// Builds the independent runs of the day, to be scheduled by `parallel::run_jobs`.
+    /// The parts reading the same file share a run, so that the file is parsed once
+    pub fn jobs(&self, options: &RunOption) -> Vec<Job> {
+        let mut runs: Vec<(PathBuf, Dataset, Vec<Part>)> = vec![];
+        for (part, data_set) in selected_runs(&self.day, self.kind() == RunKind::PerPart, options) {
+            let filename = get_applicable_filename(&self.day, part, &data_set);
+            let parts = part.map(|p| vec![p]).unwrap_or(vec![Part::Part1, Part::Part2]);
+            match runs.iter_mut().find(|(f, d, _)| *f == filename && *d == data_set) {
+                Some((_, _, run_parts)) => run_parts.extend(parts),
+                None => runs.push((filename, data_set, parts)),
+            }
+        }
+        runs.into_iter()
+            .map(|(_, data_set, parts)| {
+                let part = match (self.kind(), parts.as_slice()) {
+                    (RunKind::PerPart, [part]) => Some(*part),
+                    _ => None,
+                };
+                Job {
+                    context: Context::new(&self.day, options, part, &data_set),
+                    parts,
+                    solver: self.solver,
+                    mode: *options.get_mode(),
+                    bench_tags: options.bench_tags.clone(),
+                    timeout: options.timeout,
+                    input: options.input.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A day/dataset run, solving one or both parts
+pub struct Job {
+    context: Context,
+    parts: Vec<Part>,
+    solver: Solver,
+    mode: Mode,
+    bench_tags: Vec<String>,
+    timeout: Option<Duration>,
+    input: Option<InputSource>,
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic".to_string()
+    }
+}
+
+impl Job {
+    /// Reads and parses the dataset once, then solves each part on the parsed input.
+    /// Each phase runs in a worker thread with its own timeout: a panic is recorded as FAILED and an overrun as TIMEOUT,
+    /// for the parts of that phase only. When buffered, the logs are kept and returned to be printed at once
+    pub fn execute(self, buffered: bool) -> String {
+        let output = if buffered { Some(Arc::new(Mutex::new(String::new()))) } else { None };
+        let captured = if logger::is_capture() { Some(Arc::new(Mutex::new(vec![]))) } else { None };
+        let context = Context {
+            output: output.clone(),
+            captured,
+            ..self.context.clone()
+        };
+        let (solver, mode) = (self.solver, self.mode);
+
+        let (parts, input) = (self.parts.clone(), self.input.clone());
+        let parsed = self.guarded(&context, &self.parts, move |c| read_and_parse(&c, &parts, solver, &input, &mode));
+        if let Some((input, timings)) = parsed.flatten() {
+            let input = Arc::new(input);
+            let phases: Vec<(Context, Vec<Part>)> = match solver {
+                Solver::PerPart { .. } => self.parts.iter().map(|part| (context.with_part(*part), vec![*part])).collect(),
+                Solver::Simult { .. } => vec![(context.clone(), self.parts.clone())],
+            };
+            for (phase_context, parts) in phases {
+                // Each bench variant has its own guard, so that a failing variant doesn't hide the others
+                let variants: Vec<Context> = if phase_context.is_bench() && !self.bench_tags.is_empty() {
+                    self.bench_tags.iter().map(|tag| phase_context.with_bench_tag(tag)).collect()
+                } else {
+                    vec![phase_context.clone()]
+                };
+                let mut variant_stats = vec![];
+                for variant_context in variants {
+                    let input = input.clone();
+                    let stats = self.guarded(&variant_context, &parts, move |c| solve_parsed(c, solver, &input, timings, &mode));
+                    if let (Some(tag), Some(Some(stats))) = (variant_context.bench_tag.clone(), stats) {
+                        variant_stats.push((tag, stats));
+                    }
+                }
+                if !variant_stats.is_empty() {
+                    log_variants(&phase_context, &variant_stats);
+                }
+            }
+        }
+
+        let text = output.map(|o| o.lock().unwrap().clone()).unwrap_or_default();
+        text
+    }
+
+    /// Runs a phase in a worker thread, failing the given parts if it panics or overruns the timeout.
+    /// The abandoned thread of a timed out phase is muted
+    fn guarded<R: Send + 'static>(&self, context: &Context, parts: &[Part], phase: impl FnOnce(Context) -> R + Send + 'static) -> Option<R> {
+        let muted = Arc::new(AtomicBool::new(false));
+        let worker_context = Context {
+            muted: Some(muted.clone()),
+            ..context.clone()
+        };
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            let _ = sender.send(phase(worker_context));
+        });
+        let result = match self.timeout {
+            Some(timeout) => receiver.recv_timeout(timeout),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match result {
+            Ok(result) => {
+                let _ = worker.join();
+                Some(result)
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                muted.store(true, std::sync::atomic::Ordering::Relaxed);
+                context.fail(parts, Status::Timeout, &format!("not finished after {:?}", self.timeout.unwrap()));
+                None
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let message = worker.join().err().map(panic_message).unwrap_or_default();
+                context.fail(parts, Status::Failed, &message);
+                None
+            }
+        }
+    }
+}
+
+/// Solves a part on its test datasets (or on the real one) and asserts the expected answers, for the generated `cargo test` cases.
+/// The datasets without expected answer are skipped
+#[allow(dead_code)]
+pub fn assert_expected_answers(day: &Day, part: Part, tests: bool) {
+    let file_part = day.file_part(part);
+    let mut failures = vec![];
+    let mut nb_checked = 0;
+    for data_set in list_datasets(&day.day, file_part).into_iter().filter(|d| d.is_test() == tests) {
+        let Some(expected) = answers::expected_answer(day.day, part, &data_set) else {
+            continue;
+        };
+        nb_checked += 1;
+        match read_lines(&day.day, file_part, &data_set) {
+            Ok(lines) => {
+                let answer = day.solve_lines(part, &lines);
+                if answer != expected {
+                    failures.push(format!("{} found {} instead of {}", data_set, answer, expected));
+                }
+            }
+            Err(e) => failures.push(format!("{} {}", data_set, e)),
+        }
+    }
+    assert!(nb_checked > 0, "Day {} {:?}: no dataset with an expected answer", day.day, part);
+    assert!(failures.is_empty(), "Day {} {:?}: {}", day.day, part, failures.join(", "));
+}
+
+/// Declares the day modules, builds the registry of their `DAY` definitions (sorted by day number) and generates their tests
+#[macro_export]
+macro_rules! register_days {
+    ($($module:ident),* $(,)?) => {
+        $(mod $module;)*
+
+        pub fn all_days() -> Vec<&'static $crate::utils::Day> {
+            let mut days: Vec<&'static $crate::utils::Day> = vec![$(&$module::DAY),*];
+            days.sort_by_key(|d| d.day);
+            days
+        }
+
+        /// One test per day, part and kind of dataset, e.g. `cargo test day05` or `cargo test part2_real`,
+        /// ignored until `answers.toml` holds an expected answer for it (see `build.rs`)
+        #[cfg(test)]
+        mod answer_tests {
+            $(mod $module {
+                use $crate::utils::{assert_expected_answers, Part};
+
+                #[test]
+                #[cfg_attr(not($module = "part1_test"), ignore = "no expected answer in answers.toml")]
+                fn part1_test() {
+                    assert_expected_answers(&$crate::$module::DAY, Part::Part1, true);
+                }
+
+                #[test]
+                #[cfg_attr(not($module = "part1_real"), ignore = "no expected answer in answers.toml")]
+                fn part1_real() {
+                    assert_expected_answers(&$crate::$module::DAY, Part::Part1, false);
+                }
+
+                #[test]
+                #[cfg_attr(not($module = "part2_test"), ignore = "no expected answer in answers.toml")]
+                fn part2_test() {
+                    assert_expected_answers(&$crate::$module::DAY, Part::Part2, true);
+                }
+
+                #[test]
+                #[cfg_attr(not($module = "part2_real"), ignore = "no expected answer in answers.toml")]
+                fn part2_real() {
+                    assert_expected_answers(&$crate::$module::DAY, Part::Part2, false);
+                }
+            })*
+        }
+    };
 }
 
 #[allow(dead_code)]
@@ -495,3 +1333,62 @@ pub fn merge<A, B, C>(first: Option<A>, second: Option<B>, merger: fn(A, B) -> C
     let second = second?;
     Some(merger(first, second))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Block counting how many times it is formatted
+    struct CountedBlock(Cell<usize>);
+
+    impl Display for CountedBlock {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.0.set(self.0.get() + 1);
+            write!(f, "line 1\nline 2")
+        }
+    }
+
+    fn context_with_output(options: &RunOption) -> (Context, Arc<Mutex<String>>) {
+        let output = Arc::new(Mutex::new(String::new()));
+        let context = Context {
+            output: Some(output.clone()),
+            ..Context::new_part(&99, options, Part::Part1, &Dataset::Test)
+        };
+        return (context, output);
+    }
+
+    #[test]
+    fn debug_block_is_silent_below_debug() {
+        let (context, output) = context_with_output(&RunOption::new(&None));
+        let block = CountedBlock(Cell::new(0));
+        context.debug_block(format_args!("Map"), &block);
+        assert_eq!(block.0.get(), 0);
+        assert_eq!(*output.lock().unwrap(), "");
+    }
+
+    #[test]
+    fn debug_block_logs_each_line_in_debug() {
+        let (context, output) = context_with_output(&RunOption::new(&None).debug());
+        let block = CountedBlock(Cell::new(0));
+        context.debug_block(format_args!("Map"), &block);
+        assert_eq!(block.0.get(), 1);
+        let output = output.lock().unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("Map") && lines[2].ends_with("line 2"), "{:?}", lines);
+    }
+
+    #[test]
+    fn debug_mode_keeps_the_day_overrides() {
+        logger::init(logger::Logger {
+            day_levels: [(97, LogLevel::ERROR), (98, LogLevel::TRACE)].into_iter().collect(),
+            ..logger::Logger::default()
+        });
+        let debug = RunOption::new(&None).debug();
+        assert_eq!(debug.get_log_level(&97), LogLevel::ERROR);
+        assert_eq!(debug.get_log_level(&98), LogLevel::TRACE);
+        assert_eq!(debug.get_log_level(&96), LogLevel::DEBUG);
+        assert_eq!(RunOption::new(&None).get_log_level(&96), LogLevel::INFO);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::cli::RunArgs;
+use crate::report::{RunResult, Status};
+use crate::utils;
+
+/// Delay between two scans of the watched directories
+const POLL_DELAY: Duration = Duration::from_millis(500);
+
+/// Identifies a run as (day, part, dataset, bench tag)
+type RunId = (u8, u8, String, Option<String>);
+
+fn src_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+/// Modification times of the files of the watched directories
+fn snapshot() -> BTreeMap<PathBuf, SystemTime> {
+    [src_dir(), utils::data_dir()]
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .collect()
+}
+
+fn changed_files(before: &BTreeMap<PathBuf, SystemTime>, after: &BTreeMap<PathBuf, SystemTime>) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, modified)| before.get(*path) != Some(*modified))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(before.keys().filter(|path| !after.contains_key(*path)).cloned());
+    changed
+}
+
+/// Day of a `dayNN.rs` source or of a `day_N[...].dat` data file
+fn file_day(path: &Path) -> Option<u8> {
+    let name = path.file_name()?.to_str()?;
+    if let Some(module) = name.strip_suffix(".rs") {
+        return module.strip_prefix("day")?.parse().ok();
+    }
+    let dataset = name.strip_suffix(".dat")?.strip_prefix("day_")?;
+    dataset.split('_').next()?.parse().ok()
+}
+
+/// Days to run again after the changes: the day of a day source or data file, all the watched days otherwise.
+/// The files unrelated to the runs (e.g. the submissions history) are ignored
+fn affected_days(changed: &[PathBuf], watched: &[u8]) -> BTreeSet<u8> {
+    let mut days = BTreeSet::new();
+    for path in changed {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        let is_answers = path.file_name().is_some_and(|name| name == "answers.toml");
+        if extension != "rs" && extension != "dat" && !is_answers {
+            continue;
+        }
+        match file_day(path) {
+            Some(day) if watched.is_empty() || watched.contains(&day) => {
+                days.insert(day);
+            }
+            Some(_) => {}
+            None => days.extend(watched_days(watched)),
+        }
+    }
+    days
+}
+
+/// The days given on the command line, all the registered days otherwise
+fn watched_days(watched: &[u8]) -> Vec<u8> {
+    if watched.is_empty() {
+        crate::all_days().iter().map(|d| d.day).collect()
+    } else {
+        watched.to_vec()
+    }
+}
+
+fn build() -> bool {
+    let mut command = Command::new(env!("CARGO"));
+    command.arg("build").arg("--manifest-path").arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"));
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    println!("[WATCH] Building");
+    command.status().map(|s| s.success()).unwrap_or(false)
+}
+
+/// Runs the days with the rebuilt binary and returns their results.
+/// Only the errors of the run are shown, its results are summed up by `print_diff`
+fn run_days(exe: &Path, args: &RunArgs, days: &BTreeSet<u8>) -> Vec<RunResult> {
+    let report = std::env::temp_dir().join(format!("aoc_watch_{}.json", std::process::id()));
+    let run_args = RunArgs {
+        day: days.iter().copied().collect(),
+        report_json: Some(report.display().to_string()),
+        ..args.clone()
+    };
+    let _ = fs::remove_file(&report);
+    if let Err(e) = Command::new(exe).args(run_args.to_args()).stdout(Stdio::null()).status() {
+        println!("[WATCH] Cannot run the days: {}", e);
+        return vec![];
+    }
+    let results = fs::read_to_string(&report)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let _ = fs::remove_file(&report);
+    results.unwrap_or_else(|| {
+        println!("[WATCH] No report written by the run");
+        vec![]
+    })
+}
+
+fn run_id(result: &RunResult) -> RunId {
+    (result.day, result.part, result.dataset.clone(), result.bench_tag.clone())
+}
+
+fn status_label(result: &RunResult) -> String {
+    format!("{:?} {}", result.status, result.answer).to_uppercase()
+}
+
+/// Prints one line per run, with the previous status and answer when they changed
+fn print_diff(previous: &BTreeMap<RunId, RunResult>, results: &[RunResult]) {
+    for result in results {
+        let tag = result.bench_tag.as_ref().map(|t| format!("#{}", t)).unwrap_or_default();
+        let change = match previous.get(&run_id(result)) {
+            Some(before) if before.status != result.status || before.answer != result.answer => {
+                format!(" (was {})", status_label(before))
+            }
+            Some(_) => String::new(),
+            None => " (new)".to_string(),
+        };
+        let marker = match result.status {
+            Status::Ok => "  ",
+            Status::Unchecked | Status::Recorded => "? ",
+            _ => "! ",
+        };
+        println!(
+            "[WATCH] {}Day {:>2} part{} {}{} {}{}",
+            marker,
+            result.day,
+            result.part,
+            result.dataset,
+            tag,
+            status_label(result),
+            change
+        );
+    }
+}
+
+/// Runs the watched days, then polls the sources and the data files and runs the affected days again after each change.
+/// The days affected by a change that doesn't build are kept for the next successful build
+pub fn watch(args: &RunArgs) {
+    // Resolved before any build: once the binary is replaced, `current_exe` points to the deleted file
+    let Ok(exe) = std::env::current_exe() else {
+        println!("[WATCH] Cannot find the current executable");
+        return;
+    };
+    let watched = args.day.clone();
+    let mut previous: BTreeMap<RunId, RunResult> = BTreeMap::new();
+    let mut pending: BTreeSet<u8> = watched_days(&watched).into_iter().collect();
+    let mut files = snapshot();
+    loop {
+        if !pending.is_empty() {
+            let results = run_days(&exe, args, &pending);
+            print_diff(&previous, &results);
+            previous.extend(results.into_iter().map(|r| (run_id(&r), r)));
+            pending.clear();
+        }
+        println!("[WATCH] Waiting for changes in {} and {}", src_dir().display(), utils::data_dir().display());
+
+        loop {
+            thread::sleep(POLL_DELAY);
+            let current = snapshot();
+            let changed = changed_files(&files, &current);
+            files = current;
+            if changed.is_empty() {
+                continue;
+            }
+            // Lets the editor finish writing (e.g. several files saved at once)
+            thread::sleep(POLL_DELAY);
+            let current = snapshot();
+            let mut changed = changed;
+            changed.extend(changed_files(&files, &current));
+            files = current;
+
+            let affected = affected_days(&changed, &watched);
+            if affected.is_empty() {
+                continue;
+            }
+            pending.extend(affected);
+            let is_source = changed.iter().any(|path| path.extension().is_some_and(|e| e == "rs"));
+            if !is_source || build() {
+                break;
+            }
+            println!("[WATCH] Build failed, waiting for a fix");
+        }
+    }
+}